@@ -0,0 +1,144 @@
+//! Discovers the target PHP through php-config (or the `PHP_CONFIG` env variable)
+//! and exports its build options as cfgs:
+//!
+//! - `php_zts` for thread-safe builds, or when the `zts` feature is enabled
+//! - `php_debug` for debug builds (`--enable-debug`), or when the `debug` feature is enabled
+//! - `php_hash_file_line` for debug builds of PHP < 7.3, whose hash functions take
+//!   the caller's file and line
+//! - `php_bindings` when bindgen generated `$OUT_DIR/bindings.rs`, opt-in with
+//!   `PHP_RUST_BINDGEN=1` and a `bindgen` binary in `PATH`
+//!
+//! Without php-config the build only relies on the cargo features.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Headers declaring everything bound in `src/zend`
+const WRAPPER_H: &str = r#"
+#include "php.h"
+#include "SAPI.h"
+#include "zend_exceptions.h"
+#include "zend_ini.h"
+#include "ext/standard/info.h"
+"#;
+
+/// Oldest PHP version of each cargo feature, the newest enabled one is selected
+const VERSION_FEATURES: [(&str, u32); 5] = [
+    ("PHP83", 80300),
+    ("PHP82", 80200),
+    ("PHP81", 80100),
+    ("PHP80", 80000),
+    ("PHP74", 70400),
+];
+
+struct PhpConfig {
+    includes: String,
+    extension_dir: String,
+    vernum: u32,
+    configure_options: String,
+}
+
+impl PhpConfig {
+    fn discover() -> Option<Self> {
+        let path = env::var("PHP_CONFIG").unwrap_or_else(|_| "php-config".to_owned());
+        let query = |option: &str| -> Option<String> {
+            let output = Command::new(&path).arg(option).output().ok()?;
+            if !output.status.success() {
+                return None;
+            }
+            Some(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+        };
+        Some(PhpConfig {
+            includes: query("--includes")?,
+            extension_dir: query("--extension-dir")?,
+            vernum: query("--vernum")?.parse().ok()?,
+            configure_options: query("--configure-options")?,
+        })
+    }
+
+    fn has_option(&self, option: &str) -> bool {
+        self.configure_options.split_whitespace().any(|o| o == option || o.starts_with(&format!("{}=", option)))
+    }
+
+    fn is_zts(&self) -> bool {
+        self.has_option("--enable-zts") || self.has_option("--enable-maintainer-zts")
+    }
+
+    fn is_debug(&self) -> bool {
+        self.has_option("--enable-debug")
+    }
+}
+
+fn feature_enabled(name: &str) -> bool {
+    env::var_os(format!("CARGO_FEATURE_{}", name)).is_some()
+}
+
+/// Runs the bindgen CLI against the discovered headers, returns false if it's unavailable or fails
+fn generate_bindings(php: &PhpConfig, out_dir: &Path) -> bool {
+    let wrapper = out_dir.join("wrapper.h");
+    if fs::write(&wrapper, WRAPPER_H).is_err() {
+        return false;
+    }
+    let status = Command::new("bindgen")
+        .arg(&wrapper)
+        .arg("-o").arg(out_dir.join("bindings.rs"))
+        .args(["--allowlist-type", "(_)?(zend|zval|sapi)_.*"])
+        .arg("--no-layout-tests")
+        .arg("--")
+        .args(php.includes.split_whitespace())
+        .status();
+    matches!(status, Ok(status) if status.success())
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=PHP_CONFIG");
+    println!("cargo:rerun-if-env-changed=PHP_RUST_BINDGEN");
+    println!("cargo:rustc-check-cfg=cfg(php_zts)");
+    println!("cargo:rustc-check-cfg=cfg(php_debug)");
+    println!("cargo:rustc-check-cfg=cfg(php_hash_file_line)");
+    println!("cargo:rustc-check-cfg=cfg(php_bindings)");
+
+    let php = PhpConfig::discover();
+
+    let zts = feature_enabled("ZTS") || php.as_ref().is_some_and(PhpConfig::is_zts);
+    if zts {
+        println!("cargo:rustc-cfg=php_zts");
+    }
+
+    let debug = feature_enabled("DEBUG") || php.as_ref().is_some_and(PhpConfig::is_debug);
+    if debug {
+        println!("cargo:rustc-cfg=php_debug");
+        if !feature_enabled("PHP74") {
+            println!("cargo:rustc-cfg=php_hash_file_line");
+        }
+    }
+
+    let php = match php {
+        Some(php) => php,
+        None => return,
+    };
+    println!("cargo:rustc-env=PHP_EXTENSION_DIR={}", php.extension_dir);
+    println!("cargo:rustc-env=PHP_VERSION_ID={}", php.vernum);
+
+    let selected = VERSION_FEATURES.iter()
+        .find(|(feature, _)| feature_enabled(feature))
+        .map_or(70000, |&(_, version)| version);
+    if php.vernum / 100 != selected / 100 {
+        println!(
+            "cargo:warning=php-config reports PHP {}.{} but the crate targets PHP {}.{}, enable the matching phpXY feature",
+            php.vernum / 10000, php.vernum / 100 % 100, selected / 10000, selected / 100 % 100
+        );
+    }
+
+    if env::var_os("PHP_RUST_BINDGEN").is_some_and(|value| value == "1") {
+        let out_dir = PathBuf::from(env::var_os("OUT_DIR").expect("OUT_DIR is set by cargo"));
+        if generate_bindings(&php, &out_dir) {
+            println!("cargo:rustc-cfg=php_bindings");
+        } else {
+            println!("cargo:warning=bindgen failed, the layouts are not cross-checked");
+        }
+    }
+}
@@ -1,7 +1,7 @@
 #!/usr/bin/env bash
 
 BUILD_MODE=release
-PHP_EXT_DIR=`php -i | grep extension_dir | awk '{print $NF}'`
+PHP_EXT_DIR=`${PHP_CONFIG:-php-config} --extension-dir`
 
 cargo build --${BUILD_MODE} --example ${1} &&
-sudo cp target/${BUILD_MODE}/examples/lib${1}.so ${PHP_EXT_DIR}/rust_example_ext.so
\ No newline at end of file
+sudo cp target/${BUILD_MODE}/examples/lib${1}.so ${PHP_EXT_DIR}/rust_example_ext.so
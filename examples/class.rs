@@ -0,0 +1,57 @@
+extern crate php_rust as php;
+
+use php::zend::{Module, ZEND_ACC_PUBLIC, ZEND_ACC_STATIC, ZEND_ACC_PROTECTED};
+use php::{c_str, method, php_class, php_function, ClassBuilder, ExecuteData, Function, InfoTable, IntoRetval, Long, PhpClass, Zval};
+
+#[php_function]
+pub fn answer() -> Long {
+    42
+}
+
+#[php_class]
+#[derive(Default, Clone)]
+pub struct Counter {
+    count: Long,
+}
+
+/// Counter::increment(): int
+pub fn counter_increment(execute_data: &ExecuteData, retval: &mut Zval) {
+    let mut object = match execute_data.this().and_then(Zval::as_object) {
+        Some(object) => object,
+        None => return,
+    };
+    // SAFETY: the method holds the only reference to the counter during the call
+    if let Some(counter) = unsafe { object.downcast_mut::<Counter>() } {
+        counter.count += 1;
+        counter.count.set_retval(retval);
+    }
+}
+
+#[no_mangle]
+pub extern fn get_module() -> *mut Module {
+    let mut module = Module::new(c_str!("rust_example_ext"), c_str!("0.1.0"));
+
+    module.add_class(
+        ClassBuilder::new("RustExample")
+            .constant("VERSION", "0.1.0")
+            .property("name", "example", ZEND_ACC_PROTECTED)
+            .method(
+                Function::new_with_args(c_str!("answer"), answer, answer::arg_info())
+                    .with_flags(ZEND_ACC_PUBLIC | ZEND_ACC_STATIC)
+            )
+    );
+    module.add_class(
+        ClassBuilder::new(Counter::NAME)
+            .cloneable_object::<Counter>()
+            .method(method!("increment", counter_increment))
+    );
+    module.set_default_info();
+    module.on_info(|| {
+        InfoTable::new()
+            .colspan_header(2, "Classes")
+            .row(["RustExample", "static answer()"])
+            .row([Counter::NAME, "increment()"])
+            .render()
+    });
+    module.into_raw()
+}
@@ -0,0 +1,48 @@
+extern crate php_rust as php;
+
+use php::zend::{Module, ModuleDep, VersionRel, PHP_INI_ALL};
+use php::{c_str, php_funs, php_function, ClassBuilder, IniEntry, Long, PhpException, Zval};
+
+static GREETING: IniEntry<String> = IniEntry::new("rust_example_ext.greeting", "Hello", PHP_INI_ALL)
+    .with_validator(|greeting| !greeting.is_empty());
+
+#[php_function]
+pub fn add(a: Long, b: Option<Long>) -> Long {
+    a + b.unwrap_or(1)
+}
+
+#[php_function]
+pub fn greet(name: &str, greeting: Option<String>) -> String {
+    format!("{}, {}!", greeting.unwrap_or_else(|| GREETING.get()), name)
+}
+
+#[php_function]
+pub fn count_args(first: &Zval, rest: &[Zval]) -> Long {
+    let _ = first;
+    1 + rest.len() as Long
+}
+
+#[php_function]
+pub fn reset_to_zero(var: &mut Zval) {
+    *var = Zval::from(0);
+}
+
+#[php_function]
+pub fn divide(a: Long, b: Long) -> Result<Long, PhpException> {
+    match a.checked_div(b) {
+        Some(result) => Ok(result),
+        None => Err(PhpException::new("Division by zero").with_class("RustMathException").with_code(1)),
+    }
+}
+
+#[no_mangle]
+pub extern fn get_module() -> *mut Module {
+    let mut module = Module::new(c_str!("rust_example_ext"), c_str!("0.1.0"));
+
+    module.set_functions(php_funs![add, greet, count_args, reset_to_zero, divide]);
+    module.add_class(ClassBuilder::exception("RustMathException"));
+    module.add_ini_entry(&GREETING).expect("invalid INI default");
+    module.add_dependency(ModuleDep::required("json").with_version(VersionRel::Ge, "1.4.0"));
+    module.add_dependency(ModuleDep::optional("session"));
+    module.into_raw()
+}
@@ -1,20 +1,19 @@
 extern crate php_rust as php;
 
 use php::zend::Module;
-use php::{c_str, funs, ExecuteData, Zval, Value, ArrayApi};
+use php::{c_str, funs, ExecuteData, Zval, Value, ArrayApi, FromZval, ZArrayRef};
 
-#[no_mangle]
-pub extern fn print_array(data: &ExecuteData, _retval: &mut Zval) {
+pub fn print_array(data: &ExecuteData, _retval: &mut Zval) {
     let params = data.parse_parameters().unwrap();
-    let a = params[0].as_array().unwrap();
+    let a = <&ZArrayRef>::from_zval(&params[0]).unwrap();
 
-    for b in a.buckets_iter() {
-        match b.get_val().unwrap().value() {
+    for (_, val) in a.iter() {
+        match val.value() {
             Value::Null => println!("null"),
             Value::Bool(v) => println!("{:?}", v),
             Value::Long(v) => println!("{:?}", v),
             Value::Double(v) => println!("{:?}", v),
-            Value::String(v) => println!("{:?}", v.as_str()),
+            Value::String(v) => println!("{:?}", v.to_string_lossy()),
             v => println!("{:?}", v),
         }
     }
@@ -1,16 +1,15 @@
 extern crate php_rust as php;
 
 use php::zend::Module;
-use php::{c_str, str_index, funs, ExecuteData, Zval, Array, ArrayApi};
+use php::{c_str, str_index, funs, ExecuteData, Zval, ZArray, ArrayApi, IntoZval};
 
-#[no_mangle]
-pub extern fn array_from_rust(_data: &ExecuteData, retval: &mut Zval) {
-    let mut a = Array::new();
+pub fn array_from_rust(_data: &ExecuteData, retval: &mut Zval) {
+    let mut a = ZArray::new();
     a.insert(str_index!("foo"), 1);
     a.insert(str_index!("bar"), 2.0);
     a.insert(str_index!("baz"), "some message");
 
-    retval.set_arr(&mut a);
+    *retval = a.into_zval();
 }
 
 #[no_mangle]
@@ -3,8 +3,7 @@ extern crate php_rust as php;
 use php::zend::Module;
 use php::{c_str, funs, ExecuteData, Zval};
 
-#[no_mangle]
-pub extern fn hello_from_rust(_data: &ExecuteData, retval: &mut Zval) {
+pub fn hello_from_rust(_data: &ExecuteData, retval: &mut Zval) {
     retval.set_new_str("Hello from Rust!", false);
 }
 
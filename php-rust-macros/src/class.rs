@@ -0,0 +1,44 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{meta::ParseNestedMeta, Error, ItemStruct, LitStr, Result};
+
+#[derive(Default)]
+pub struct ClassArgs {
+    name: Option<LitStr>,
+}
+
+impl ClassArgs {
+    pub fn parse(&mut self, meta: ParseNestedMeta) -> Result<()> {
+        if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("unsupported php_class argument"))
+        }
+    }
+}
+
+pub fn expand(args: ClassArgs, item: ItemStruct) -> Result<TokenStream> {
+    if !item.generics.params.is_empty() {
+        return Err(Error::new_spanned(&item.generics, "php class can't be generic"));
+    }
+
+    let ident = &item.ident;
+    let name = match args.name {
+        Some(name) => name.value(),
+        None => ident.to_string(),
+    };
+
+    Ok(quote! {
+        #item
+
+        impl ::php_rust::PhpClass for #ident {
+            const NAME: &'static str = #name;
+
+            fn class_slot() -> &'static ::php_rust::ClassSlot {
+                static SLOT: ::php_rust::ClassSlot = ::php_rust::ClassSlot::new();
+                &SLOT
+            }
+        }
+    })
+}
@@ -0,0 +1,173 @@
+use proc_macro2::TokenStream;
+use quote::{quote, format_ident};
+use syn::{Error, FnArg, ItemFn, Pat, Result, Type, TypePath};
+
+enum ArgKind {
+    Required,
+    Nullable,
+    ByRef,
+    Variadic,
+}
+
+struct Arg {
+    name: String,
+    ty: Type,
+    kind: ArgKind,
+}
+
+impl Arg {
+    fn parse(arg: &FnArg) -> Result<Arg> {
+        let arg = match arg {
+            FnArg::Typed(arg) => arg,
+            FnArg::Receiver(receiver) =>
+                return Err(Error::new_spanned(receiver, "php function can't take `self`")),
+        };
+        let name = match &*arg.pat {
+            Pat::Ident(pat) => pat.ident.to_string(),
+            pat => return Err(Error::new_spanned(pat, "php function argument must be an identifier")),
+        };
+        let ty = (*arg.ty).clone();
+        let kind = match &ty {
+            Type::Reference(r) if r.mutability.is_some() && is_path_to(&r.elem, "Zval") => ArgKind::ByRef,
+            Type::Reference(r) if r.mutability.is_none() => match &*r.elem {
+                Type::Slice(slice) if is_path_to(&slice.elem, "Zval") => ArgKind::Variadic,
+                _ => ArgKind::Required,
+            },
+            ty if is_path_to(ty, "Option") => ArgKind::Nullable,
+            _ => ArgKind::Required,
+        };
+
+        Ok(Arg { name, ty, kind })
+    }
+}
+
+fn is_path_to(ty: &Type, ident: &str) -> bool {
+    match ty {
+        Type::Path(TypePath { qself: None, path }) =>
+            path.segments.last().is_some_and(|segment| segment.ident == ident),
+        _ => false,
+    }
+}
+
+pub fn expand(item: ItemFn) -> Result<TokenStream> {
+    let sig = &item.sig;
+    if !sig.generics.params.is_empty() {
+        return Err(Error::new_spanned(&sig.generics, "php function can't be generic"));
+    }
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(Error::new_spanned(asyncness, "php function can't be async"));
+    }
+
+    let args = sig.inputs.iter()
+        .map(Arg::parse)
+        .collect::<Result<Vec<_>>>()?;
+
+    let variadic = match args.iter().position(|arg| matches!(arg.kind, ArgKind::Variadic)) {
+        Some(pos) if pos + 1 != args.len() =>
+            return Err(Error::new_spanned(&sig.inputs[pos], "variadic argument must be the last one")),
+        pos => pos.is_some(),
+    };
+    let required = args.iter()
+        .rposition(|arg| matches!(arg.kind, ArgKind::Required | ArgKind::ByRef))
+        .map_or(0, |pos| pos + 1);
+    let max = if variadic {
+        quote! { None }
+    } else {
+        let max = args.len();
+        quote! { Some(#max) }
+    };
+
+    let name = &sig.ident;
+    let fname = name.to_string();
+    let vis = &item.vis;
+    let attrs = &item.attrs;
+
+    let mut bindings = Vec::with_capacity(args.len());
+    let mut arg_infos = Vec::with_capacity(args.len());
+    for (idx, arg) in args.iter().enumerate() {
+        let var = format_ident!("arg{}", idx);
+        let num = idx + 1;
+        let ty = &arg.ty;
+
+        bindings.push(match arg.kind {
+            ArgKind::Required => quote! {
+                let #var = match ::php_rust::arg_from_zval::<#ty>(#fname, #num, unsafe { &*argv.add(#idx) }) {
+                    Some(value) => value,
+                    None => return,
+                };
+            },
+            ArgKind::Nullable => quote! {
+                let #var = if #idx < argc {
+                    match ::php_rust::arg_from_zval::<#ty>(#fname, #num, unsafe { &*argv.add(#idx) }) {
+                        Some(value) => value,
+                        None => return,
+                    }
+                } else {
+                    None
+                };
+            },
+            ArgKind::ByRef => quote! {
+                let #var = unsafe { &mut *argv.add(#idx) }.dereference_mut();
+            },
+            ArgKind::Variadic => quote! {
+                let #var: &[::php_rust::Zval] = if #idx < argc {
+                    unsafe { ::std::slice::from_raw_parts(argv.add(#idx), argc - #idx) }
+                } else {
+                    &[]
+                };
+            },
+        });
+
+        let arg_name = &arg.name;
+        let (allow_null, is_variadic, by_ref) = match arg.kind {
+            ArgKind::Required => (0u8, 0u8, 0u8),
+            ArgKind::Nullable => (1, 0, 0),
+            ArgKind::ByRef => (0, 0, 1),
+            ArgKind::Variadic => (0, 1, 0),
+        };
+        arg_infos.push(quote! {
+            ::php_rust::ArgInfo::new(::php_rust::c_str!(#arg_name), #allow_null, #is_variadic, #by_ref)
+        });
+    }
+    let vars = (0..args.len()).map(|idx| format_ident!("arg{}", idx));
+
+    let inner = ItemFn {
+        attrs: Vec::new(),
+        vis: syn::Visibility::Inherited,
+        sig: item.sig.clone(),
+        block: item.block.clone(),
+    };
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis extern "C" fn #name(execute_data: &::php_rust::ExecuteData, retval: &mut ::php_rust::Zval) {
+            #inner
+
+            ::php_rust::catch_panic(::php_rust::PanicContext::Request, || {
+                let args = match execute_data.parse_parameters() {
+                    Some(args) => args,
+                    None => return,
+                };
+                let argc = args.len();
+                if !::php_rust::check_num_args(#fname, argc, #required, #max) {
+                    return;
+                }
+                let argv = args.as_mut_ptr();
+                #(#bindings)*
+
+                ::php_rust::IntoRetval::set_retval(#name(#(#vars),*), retval);
+            });
+        }
+
+        #[doc(hidden)]
+        #[allow(non_snake_case)]
+        #vis mod #name {
+            pub fn arg_info() -> Box<[::php_rust::ArgInfo]> {
+                Box::new([
+                    ::php_rust::ArgInfo::info(#required, 0),
+                    #(#arg_infos,)*
+                ])
+            }
+        }
+    })
+}
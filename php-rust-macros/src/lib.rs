@@ -0,0 +1,45 @@
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, ItemFn, ItemStruct};
+
+mod class;
+mod function;
+
+/// Turns an ordinary Rust function into a PHP function handler.
+///
+/// Generates an `extern fn` with the same name that parses the arguments from `ExecuteData`,
+/// calls the original function and stores its result into `retval`, plus the matching
+/// `ArgInfo` table. Register such functions with `php_funs![]`. Panics are caught and
+/// turned into PHP errors by the panic handler.
+///
+/// Parameter types:
+/// * `T: FromZval` - required argument,
+/// * `Option<T>` - nullable argument, optional when trailing,
+/// * `&mut Zval` - argument passed by reference,
+/// * `&[Zval]` - variadic arguments, must be the last one.
+#[proc_macro_attribute]
+pub fn php_function(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as ItemFn);
+
+    function::expand(item)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Implements `PhpClass` for a struct, so it can be stored inside PHP objects.
+///
+/// The class is named after the struct unless `#[php_class(name = "Vendor\\Name")]` is given.
+/// Register it with `ClassBuilder::new(T::NAME).object::<T>()`, or `cloneable_object::<T>()`
+/// for `Clone` types, and get the value back with `Zval::downcast_ref` / `downcast_mut`.
+#[proc_macro_attribute]
+pub fn php_class(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut args = class::ClassArgs::default();
+    let parser = syn::meta::parser(|meta| args.parse(meta));
+    parse_macro_input!(attr with parser);
+    let item = parse_macro_input!(item as ItemStruct);
+
+    class::expand(args, item)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
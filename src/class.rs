@@ -0,0 +1,365 @@
+use std::{fmt, error, ptr};
+use std::collections::BTreeMap;
+use std::os::raw::{c_char, c_int};
+use std::sync::{Mutex, PoisonError};
+use crate::{zend, ExecuteData, Function, ToSafe, ZString, Long, Double, PhpClass, ObjectHooks};
+
+/// Classes registered by this extension, by lowercased name
+static CLASSES: Mutex<BTreeMap<String, usize>> = Mutex::new(BTreeMap::new());
+
+#[repr(C)]
+pub struct ClassEntry(zend::ClassEntry);
+
+impl ClassEntry {
+    /// Class registered by this extension or known to the engine. User classes only live
+    /// until the end of the request, so the entry is borrowed for the running call.
+    #[inline]
+    pub fn lookup<'a>(_execute_data: &'a ExecuteData, name: &str) -> Option<&'a ClassEntry> {
+        Self::lookup_raw(name).map(|ce| unsafe { (*ce).as_safe() })
+    }
+
+    /// Entry of a class registered by this extension or known to the engine, it may be
+    /// autoloaded. Entries of user classes are freed at the end of the request.
+    pub fn lookup_raw(name: &str) -> Option<*mut zend::ClassEntry> {
+        let registered = CLASSES.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&name.to_lowercase())
+            .copied();
+        let ce = match registered {
+            Some(ce) => ce as *mut zend::ClassEntry,
+            None => unsafe {
+                let name = zend::zend_string::init(name, false);
+                let ce = zend::zend_lookup_class(name);
+                // autoloaders may keep a reference to the name
+                zend::zend_string::release(name);
+                ce
+            },
+        };
+        if ce.is_null() {
+            None
+        } else {
+            Some(ce)
+        }
+    }
+
+    #[inline]
+    pub fn name(&self) -> ZString {
+        self.0.name.into_safe()
+    }
+
+    #[inline]
+    pub fn parent(&self) -> Option<&ClassEntry> {
+        unsafe { self.0.parent.as_ref() }.map(|ce| ce.as_safe())
+    }
+
+    #[inline]
+    pub fn flags(&self) -> u32 {
+        self.0.ce_flags
+    }
+
+    /// Whether this class is the same as `other`, extends or implements it
+    #[inline]
+    pub fn instance_of(&self, other: &ClassEntry) -> bool {
+        ptr::eq(self, other) || unsafe { zend::instanceof_function(&self.0, &other.0) }
+    }
+
+    #[inline]
+    pub fn as_raw(&self) -> *const zend::ClassEntry {
+        &self.0 as *const _
+    }
+
+    #[inline]
+    pub fn as_raw_mut(&mut self) -> *mut zend::ClassEntry {
+        &mut self.0 as *mut _
+    }
+}
+
+impl ToSafe for zend::ClassEntry {
+    type SafeType = ClassEntry;
+}
+
+/// Class given by its entry or by name, resolved on registration
+#[derive(Debug, Clone)]
+pub enum ClassRef {
+    Entry(*const zend::ClassEntry),
+    Name(String),
+}
+
+impl ClassRef {
+    pub(crate) fn resolve(&self) -> Result<*mut zend::ClassEntry, ClassError> {
+        match self {
+            ClassRef::Entry(ce) => Ok(*ce as *mut _),
+            ClassRef::Name(name) => ClassEntry::lookup_raw(name)
+                .ok_or_else(|| ClassError::UnknownClass(name.clone())),
+        }
+    }
+}
+
+impl From<&'static ClassEntry> for ClassRef {
+    fn from(from: &'static ClassEntry) -> Self {
+        ClassRef::Entry(from.as_raw())
+    }
+}
+
+impl From<&str> for ClassRef {
+    fn from(from: &str) -> Self {
+        ClassRef::Name(from.to_owned())
+    }
+}
+
+impl From<String> for ClassRef {
+    fn from(from: String) -> Self {
+        ClassRef::Name(from)
+    }
+}
+
+/// Default value of a property or value of a class constant
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClassValue {
+    Null,
+    Bool(bool),
+    Long(Long),
+    Double(Double),
+    String(String),
+}
+
+impl From<()> for ClassValue {
+    fn from(_: ()) -> Self {
+        ClassValue::Null
+    }
+}
+
+impl From<bool> for ClassValue {
+    fn from(from: bool) -> Self {
+        ClassValue::Bool(from)
+    }
+}
+
+impl From<Long> for ClassValue {
+    fn from(from: Long) -> Self {
+        ClassValue::Long(from)
+    }
+}
+
+impl From<Double> for ClassValue {
+    fn from(from: Double) -> Self {
+        ClassValue::Double(from)
+    }
+}
+
+impl From<&str> for ClassValue {
+    fn from(from: &str) -> Self {
+        ClassValue::String(from.to_owned())
+    }
+}
+
+impl From<String> for ClassValue {
+    fn from(from: String) -> Self {
+        ClassValue::String(from)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClassError {
+    /// Parent class or interface is not registered
+    UnknownClass(String),
+    /// Engine refused to register the class
+    Registration(String),
+}
+
+impl fmt::Display for ClassError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClassError::UnknownClass(name) => write!(f, "Class '{}' not found", name),
+            ClassError::Registration(name) => write!(f, "Unable to register class '{}'", name),
+        }
+    }
+}
+
+impl error::Error for ClassError {}
+
+pub struct ClassBuilder {
+    name: String,
+    flags: u32,
+    parent: Option<ClassRef>,
+    interfaces: Vec<ClassRef>,
+    methods: Vec<Function>,
+    properties: Vec<(String, ClassValue, u32)>,
+    constants: Vec<(String, ClassValue)>,
+    object: Option<ObjectHooks>,
+}
+
+/// Builders are only registered on the startup thread
+unsafe impl Send for ClassBuilder {}
+
+impl ClassBuilder {
+    pub fn new<N: Into<String>>(name: N) -> Self {
+        ClassBuilder {
+            name: name.into(),
+            flags: 0,
+            parent: None,
+            interfaces: Vec::new(),
+            methods: Vec::new(),
+            properties: Vec::new(),
+            constants: Vec::new(),
+            object: None,
+        }
+    }
+
+    pub fn interface<N: Into<String>>(name: N) -> Self {
+        Self::new(name).with_flags(zend::ZEND_ACC_INTERFACE)
+    }
+
+    /// Exception class extending `Exception`, use `extends` to pick another parent
+    pub fn exception<N: Into<String>>(name: N) -> Self {
+        Self::new(name).extends("Exception")
+    }
+
+    /// ZEND_ACC_* class flags, e.g. ZEND_ACC_FINAL or ZEND_ACC_EXPLICIT_ABSTRACT_CLASS
+    #[inline]
+    pub fn with_flags(mut self, flags: u32) -> Self {
+        self.flags |= flags;
+        self
+    }
+
+    #[inline]
+    pub fn extends<C: Into<ClassRef>>(mut self, parent: C) -> Self {
+        self.parent = Some(parent.into());
+        self
+    }
+
+    #[inline]
+    pub fn implements<C: Into<ClassRef>>(mut self, interface: C) -> Self {
+        self.interfaces.push(interface.into());
+        self
+    }
+
+    /// Method with ZEND_ACC_* flags set by `Function::with_flags`, public by default.
+    /// Build the entry with `method!` or from a `#[php_function]` handler: both catch panics,
+    /// a bare `extern fn` passed to `Function::new` would unwind into the engine.
+    #[inline]
+    pub fn method(mut self, method: Function) -> Self {
+        self.methods.push(method);
+        self
+    }
+
+    /// Methods table, as built by `funs!` or `php_funs!`
+    #[inline]
+    pub fn methods(mut self, methods: Box<[Function]>) -> Self {
+        self.methods.extend(methods.into_vec().into_iter().filter(|f| !f.is_end()));
+        self
+    }
+
+    /// Property with ZEND_ACC_PUBLIC, ZEND_ACC_PROTECTED or ZEND_ACC_PRIVATE access and
+    /// optional ZEND_ACC_STATIC flag
+    #[inline]
+    pub fn property<N, V>(mut self, name: N, default: V, flags: u32) -> Self
+    where
+        N: Into<String>,
+        V: Into<ClassValue>,
+    {
+        self.properties.push((name.into(), default.into(), flags));
+        self
+    }
+
+    #[inline]
+    pub fn constant<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: Into<String>,
+        V: Into<ClassValue>,
+    {
+        self.constants.push((name.into(), value.into()));
+        self
+    }
+
+    /// Objects of the class hold a `T` created with `T::default()`, cloning them is not allowed
+    #[inline]
+    pub fn object<T: PhpClass + Default>(mut self) -> Self {
+        self.object = Some(ObjectHooks::new::<T>());
+        self
+    }
+
+    /// Objects of the class hold a `T`, `clone` clones it along with the properties
+    #[inline]
+    pub fn cloneable_object<T: PhpClass + Default + Clone>(mut self) -> Self {
+        self.object = Some(ObjectHooks::cloneable::<T>());
+        self
+    }
+
+    /// Registers the class, must be called during module startup
+    pub fn register(self) -> Result<&'static mut ClassEntry, ClassError> {
+        let parent = match &self.parent {
+            Some(parent) => parent.resolve()?,
+            None => ptr::null_mut(),
+        };
+        let interfaces = self.interfaces.iter()
+            .map(ClassRef::resolve)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut methods = self.methods;
+        methods.push(Function::end());
+        let methods = Box::into_raw(methods.into_boxed_slice()) as *const zend::FunctionEntry;
+
+        unsafe {
+            let mut class = zend::ClassEntry::default();
+            let name = zend::zend_string::init(&self.name, true);
+            class.name = match zend::zend_new_interned_string {
+                Some(intern) => intern(name),
+                None => name,
+            };
+            class.info.internal.builtin_functions = methods;
+
+            let ce = if self.flags & zend::ZEND_ACC_INTERFACE != 0 {
+                zend::zend_register_internal_interface(&mut class)
+            } else {
+                zend::zend_register_internal_class_ex(&mut class, parent)
+            };
+            if ce.is_null() {
+                return Err(ClassError::Registration(self.name));
+            }
+            (*ce).ce_flags |= self.flags;
+            if let Some(object) = &self.object {
+                object.install(ce);
+            }
+
+            for interface in interfaces {
+                zend::zend_class_implements(ce, 1, interface);
+            }
+            for (name, value, flags) in &self.properties {
+                declare_property(ce, name, value, *flags as c_int);
+            }
+            for (name, value) in &self.constants {
+                declare_constant(ce, name, value);
+            }
+
+            CLASSES.lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .insert(self.name.to_lowercase(), ce as usize);
+            Ok((*ce).as_safe_mut())
+        }
+    }
+}
+
+unsafe fn declare_property(ce: *mut zend::ClassEntry, name: &str, value: &ClassValue, access: c_int) {
+    let (name, len) = (name.as_ptr() as *const c_char, name.len());
+    match value {
+        ClassValue::Null => zend::zend_declare_property_null(ce, name, len, access),
+        ClassValue::Bool(v) => zend::zend_declare_property_bool(ce, name, len, *v as Long, access),
+        ClassValue::Long(v) => zend::zend_declare_property_long(ce, name, len, *v, access),
+        ClassValue::Double(v) => zend::zend_declare_property_double(ce, name, len, *v, access),
+        ClassValue::String(v) =>
+            zend::zend_declare_property_stringl(ce, name, len, v.as_ptr() as *const _, v.len(), access),
+    };
+}
+
+unsafe fn declare_constant(ce: *mut zend::ClassEntry, name: &str, value: &ClassValue) {
+    let (name, len) = (name.as_ptr() as *const c_char, name.len());
+    match value {
+        ClassValue::Null => zend::zend_declare_class_constant_null(ce, name, len),
+        ClassValue::Bool(v) => zend::zend_declare_class_constant_bool(ce, name, len, *v as zend::Bool),
+        ClassValue::Long(v) => zend::zend_declare_class_constant_long(ce, name, len, *v),
+        ClassValue::Double(v) => zend::zend_declare_class_constant_double(ce, name, len, *v),
+        ClassValue::String(v) =>
+            zend::zend_declare_class_constant_stringl(ce, name, len, v.as_ptr() as *const _, v.len()),
+    };
+}
@@ -0,0 +1,100 @@
+use std::{fmt, error};
+use std::ffi::CString;
+use crate::{c_str, zend, ClassRef, ClassError, ArgError, Long};
+
+/// Exception to be thrown into PHP, `Exception` unless another class is given
+#[derive(Debug, Clone)]
+pub struct PhpException {
+    class: Option<ClassRef>,
+    message: String,
+    code: Long,
+}
+
+impl PhpException {
+    pub fn new<M: Into<String>>(message: M) -> Self {
+        PhpException {
+            class: None,
+            message: message.into(),
+            code: 0,
+        }
+    }
+
+    /// `Error`, thrown by the engine itself
+    pub fn error<M: Into<String>>(message: M) -> Self {
+        Self::new(message).with_class(ClassRef::Entry(unsafe { zend::zend_ce_error }))
+    }
+
+    pub fn type_error<M: Into<String>>(message: M) -> Self {
+        Self::new(message).with_class(ClassRef::Entry(unsafe { zend::zend_ce_type_error }))
+    }
+
+    /// Class extending `Throwable`, either an entry or a name looked up when thrown
+    #[inline]
+    pub fn with_class<C: Into<ClassRef>>(mut self, class: C) -> Self {
+        self.class = Some(class.into());
+        self
+    }
+
+    #[inline]
+    pub fn with_code(mut self, code: Long) -> Self {
+        self.code = code;
+        self
+    }
+
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    #[inline]
+    pub fn code(&self) -> Long {
+        self.code
+    }
+
+    /// Throws the exception, it is raised once the current handler returns.
+    /// `Error` is thrown instead if the class is unknown.
+    pub fn throw(self) {
+        let ce = match &self.class {
+            Some(class) => class.resolve(),
+            None => Ok(unsafe { zend::zend_exception_get_default() }),
+        };
+        let (ce, message, code) = match ce {
+            Ok(ce) => (ce, self.message, self.code),
+            Err(err) => (unsafe { zend::zend_ce_error }, err.to_string(), 0),
+        };
+        let message = CString::new(message.replace('\0', "\\0")).unwrap_or_default();
+        unsafe { zend::zend_throw_exception_ex(ce, code, c_str!("%s"), message.as_ptr()) };
+    }
+}
+
+impl fmt::Display for PhpException {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl error::Error for PhpException {}
+
+impl From<&str> for PhpException {
+    fn from(from: &str) -> Self {
+        PhpException::new(from)
+    }
+}
+
+impl From<String> for PhpException {
+    fn from(from: String) -> Self {
+        PhpException::new(from)
+    }
+}
+
+impl From<ArgError> for PhpException {
+    fn from(from: ArgError) -> Self {
+        PhpException::type_error(from.to_string())
+    }
+}
+
+impl From<ClassError> for PhpException {
+    fn from(from: ClassError) -> Self {
+        PhpException::error(from.to_string())
+    }
+}
@@ -1,36 +1,65 @@
+use std::ffi::CString;
 use libc::*;
-use crate::{ExecuteData, Zval};
+use crate::{c_str, zend, ExecuteData, Zval, FromZval, IntoZval, ArgError, PhpException};
 
 pub(crate) type StartupFunc = extern fn (type_: c_int, module_number: c_int) -> c_int;
 pub(crate) type ShutdownFunc = extern fn (type_: c_int, module_number: c_int) -> c_int;
-pub(crate) type InfoFunc = extern fn () ;
-pub(crate) type GlobalsCtorFunc = extern fn (global: *const c_void) -> c_void;
-pub(crate) type GlobalsDtorFunc = extern fn (global: *const c_void) -> c_void;
+pub(crate) type InfoFunc = extern "C" fn (zend_module: *mut zend::Module);
+pub(crate) type GlobalsCtorFunc = unsafe extern "C" fn (global: *mut c_void);
+pub(crate) type GlobalsDtorFunc = unsafe extern "C" fn (global: *mut c_void);
 pub(crate) type PostDeactivateFunc = extern fn () -> c_int;
 pub(crate) type HandlerFunc = extern fn (execute_data: &ExecuteData, retval: &mut Zval);
 
 //pub struct ExecuteData {}
 
-#[repr(C)]
-pub struct ArgInfo {
-    name: *const c_char,
-    class_name: *const c_char,
-    type_hint: c_uchar,
-    pass_by_reference: c_uchar,
-    allow_null: c_uchar,
-    is_variadic: c_uchar,
-}
+/// Untyped argument of an internal function, `allow_null` only applies to typed arguments
+/// and is ignored since PHP 7.4
+#[repr(transparent)]
+pub struct ArgInfo(zend::zend_internal_arg_info);
 
 impl ArgInfo {
     pub fn new(name: *const c_char, allow_null: c_uchar, is_variadic: c_uchar, pass_by_reference: c_uchar) -> ArgInfo {
-        ArgInfo {
+        ArgInfo::flags(name, pass_by_reference, is_variadic, allow_null)
+    }
+
+    /// Leading element of an arg info table (zend_internal_function_info)
+    pub fn info(required_num_args: usize, return_reference: c_uchar) -> ArgInfo {
+        ArgInfo::flags(required_num_args as *const c_char, return_reference, 0, 0)
+    }
+
+    #[cfg(not(feature = "php74"))]
+    fn flags(name: *const c_char, by_ref: c_uchar, is_variadic: c_uchar, allow_null: c_uchar) -> ArgInfo {
+        ArgInfo(zend::zend_internal_arg_info {
             name,
             class_name: std::ptr::null(),
             type_hint: 0,
-            pass_by_reference,
+            pass_by_reference: by_ref,
             allow_null,
             is_variadic,
+        })
+    }
+
+    #[cfg(all(feature = "php74", not(feature = "php80")))]
+    fn flags(name: *const c_char, by_ref: c_uchar, is_variadic: c_uchar, _allow_null: c_uchar) -> ArgInfo {
+        ArgInfo(zend::zend_internal_arg_info {
+            name,
+            type_: 0,
+            pass_by_reference: by_ref,
+            is_variadic,
+        })
+    }
+
+    #[cfg(feature = "php80")]
+    fn flags(name: *const c_char, by_ref: c_uchar, is_variadic: c_uchar, _allow_null: c_uchar) -> ArgInfo {
+        let mut type_mask = (by_ref as u32) << zend::_ZEND_SEND_MODE_SHIFT;
+        if is_variadic != 0 {
+            type_mask |= zend::_ZEND_IS_VARIADIC_BIT;
         }
+        ArgInfo(zend::zend_internal_arg_info {
+            name,
+            type_: zend::zend_arg_type { ptr: std::ptr::null_mut(), type_mask },
+            default_value: std::ptr::null(),
+        })
     }
 }
 
@@ -66,6 +95,16 @@ impl Function {
         }
     }
 
+    /// ZEND_ACC_* flags, used for class methods
+    pub fn with_flags(mut self, flags: u32) -> Function {
+        self.flags = flags;
+        self
+    }
+
+    pub(crate) fn is_end(&self) -> bool {
+        self.fname.is_null()
+    }
+
     pub fn end() -> Function {
         Function {
             fname: std::ptr::null(),
@@ -76,3 +115,55 @@ impl Function {
         }
     }
 }
+
+/// Return value of a `#[php_function]` handler
+pub trait IntoRetval {
+    fn set_retval(self, retval: &mut Zval);
+}
+
+impl<T: IntoZval> IntoRetval for T {
+    #[inline]
+    fn set_retval(self, retval: &mut Zval) {
+        *retval = self.into_zval();
+    }
+}
+
+/// `Err` is thrown as an exception, leaving `null` as the return value
+impl<T: IntoZval, E: Into<PhpException>> IntoRetval for Result<T, E> {
+    #[inline]
+    fn set_retval(self, retval: &mut Zval) {
+        match self {
+            Ok(value) => value.set_retval(retval),
+            Err(err) => err.into().throw(),
+        }
+    }
+}
+
+fn warning(message: String) {
+    let message = CString::new(message).unwrap_or_default();
+    unsafe { zend::zend_error(zend::E_WARNING, c_str!("%s"), message.as_ptr()) };
+}
+
+/// Checks the number of passed arguments, emits a warning on mismatch
+#[doc(hidden)]
+pub fn check_num_args(fname: &str, given: usize, min: usize, max: Option<usize>) -> bool {
+    let (expects, expected) = match max {
+        Some(max) if given > max => (if min == max { "exactly" } else { "at most" }, max),
+        _ if given < min => (if max == Some(min) { "exactly" } else { "at least" }, min),
+        _ => return true,
+    };
+    warning(format!(
+        "{}() expects {} {} parameter{}, {} given",
+        fname, expects, expected, if expected == 1 { "" } else { "s" }, given
+    ));
+    false
+}
+
+/// Converts an argument, emits a warning if it has a wrong type
+#[doc(hidden)]
+pub fn arg_from_zval<'a, T: FromZval<'a>>(fname: &str, num: usize, zv: &'a Zval) -> Option<T> {
+    T::from_zval(zv)
+        .ok_or_else(|| ArgError::wrong_type::<T>(num, zv))
+        .map_err(|err| warning(format!("{}() {}", fname, err)))
+        .ok()
+}
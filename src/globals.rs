@@ -0,0 +1,124 @@
+use std::{mem, ptr};
+use std::cell::{RefCell, Ref, RefMut};
+use std::marker::PhantomData;
+use std::os::raw::c_void;
+use crate::{catch_panic, PanicContext};
+#[cfg(php_zts)]
+use std::sync::atomic::{AtomicI32, Ordering};
+#[cfg(php_zts)]
+use crate::zend;
+#[cfg(not(php_zts))]
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+/// Per-thread value stored by the engine, `None` until the ctor has run
+type Storage<T> = Option<RefCell<T>>;
+
+/// Module globals holding a `T`, created with `T::default()` on module startup
+/// (for every thread in ZTS builds) and dropped on module shutdown.
+///
+/// ```ignore
+/// static GLOBALS: ModuleGlobals<Counters> = ModuleGlobals::new();
+///
+/// module.set_globals(&GLOBALS);
+/// GLOBALS.globals_mut().requests += 1;
+/// ```
+pub struct ModuleGlobals<T> {
+    /// Storage allocated on `Module::set_globals`
+    #[cfg(not(php_zts))]
+    ptr: AtomicPtr<c_void>,
+    /// Resource id allocated by the engine on module registration
+    #[cfg(php_zts)]
+    id: AtomicI32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+/// Every thread only accesses its own storage
+unsafe impl<T> Sync for ModuleGlobals<T> {}
+
+impl<T: Default + 'static> ModuleGlobals<T> {
+    pub const fn new() -> Self {
+        ModuleGlobals {
+            #[cfg(not(php_zts))]
+            ptr: AtomicPtr::new(ptr::null_mut()),
+            #[cfg(php_zts)]
+            id: AtomicI32::new(0),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Borrows the globals of the current thread, panics if they are mutably borrowed
+    /// or not initialized yet
+    #[inline]
+    pub fn globals(&self) -> Ref<'_, T> {
+        self.cell().borrow()
+    }
+
+    /// Mutably borrows the globals of the current thread, panics if they are borrowed
+    /// or not initialized yet
+    #[inline]
+    pub fn globals_mut(&self) -> RefMut<'_, T> {
+        self.cell().borrow_mut()
+    }
+
+    fn cell(&self) -> &RefCell<T> {
+        unsafe { self.storage().as_ref() }
+            .and_then(Option::as_ref)
+            .expect("Module globals are not initialized")
+    }
+
+    #[cfg(not(php_zts))]
+    #[inline]
+    fn storage(&self) -> *const Storage<T> {
+        self.ptr.load(Ordering::Acquire) as *const _
+    }
+
+    #[cfg(php_zts)]
+    #[inline]
+    fn storage(&self) -> *const Storage<T> {
+        match self.id.load(Ordering::Acquire) {
+            0 => ptr::null(),
+            id => unsafe { zend::TSRMG_BULK(id) as *const _ },
+        }
+    }
+
+    #[inline]
+    pub(crate) fn size() -> usize {
+        mem::size_of::<Storage<T>>()
+    }
+
+    /// Value for the `globals_ptr` field of the module entry
+    #[cfg(not(php_zts))]
+    pub(crate) fn register(&'static self) -> *mut c_void {
+        let storage = Box::into_raw(Box::new(None::<RefCell<T>>)) as *mut c_void;
+        self.ptr.store(storage, Ordering::Release);
+        storage
+    }
+
+    /// Value for the `globals_id_ptr` field of the module entry, TSRM allocates the storage
+    #[cfg(php_zts)]
+    pub(crate) fn register(&'static self) -> *mut c_void {
+        // TSRM allocates with malloc, which doesn't guarantee larger alignment
+        assert!(mem::align_of::<Storage<T>>() <= 16, "Module globals alignment is too large");
+        self.id.as_ptr() as *mut c_void
+    }
+
+    pub(crate) unsafe extern "C" fn ctor(global: *mut c_void) {
+        let storage = global as *mut Storage<T>;
+        ptr::write(storage, None);
+        if let Some(value) = catch_panic(PanicContext::Startup, T::default) {
+            *storage = Some(RefCell::new(value));
+        }
+    }
+
+    pub(crate) unsafe extern "C" fn dtor(global: *mut c_void) {
+        // later `globals()` calls during shutdown find `None` instead of a dropped value
+        let value = ptr::replace(global as *mut Storage<T>, None);
+        catch_panic(PanicContext::Shutdown, || drop(value));
+    }
+}
+
+impl<T: Default + 'static> Default for ModuleGlobals<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,117 @@
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int};
+use crate::zend;
+
+enum InfoRow {
+    Header(Vec<CString>),
+    Row(Vec<CString>),
+    Colspan(usize, CString),
+}
+
+/// phpinfo() table, the engine renders it as HTML or as plain text in the CLI
+/// and escapes the cells for HTML output
+///
+/// ```ignore
+/// module.on_info(|| {
+///     InfoTable::new()
+///         .header(["rust_ext support", "enabled"])
+///         .row(["Version", "0.1.0"])
+///         .render()
+/// });
+/// ```
+#[derive(Default)]
+pub struct InfoTable {
+    rows: Vec<InfoRow>,
+}
+
+impl InfoTable {
+    /// Tables are limited to `MAX_COLUMNS` columns
+    pub const MAX_COLUMNS: usize = 8;
+
+    pub fn new() -> Self {
+        InfoTable { rows: Vec::new() }
+    }
+
+    /// Whether phpinfo() renders HTML, it's plain text in the CLI. The table functions switch
+    /// formats themselves, this is for content printed around the table.
+    #[inline]
+    pub fn is_html() -> bool {
+        unsafe { zend::sapi_module.phpinfo_as_text == 0 }
+    }
+
+    #[inline]
+    pub fn header<I, S>(mut self, cells: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.rows.push(InfoRow::Header(cells_of(cells)));
+        self
+    }
+
+    #[inline]
+    pub fn row<I, S>(mut self, cells: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.rows.push(InfoRow::Row(cells_of(cells)));
+        self
+    }
+
+    /// Header spanning `columns` columns
+    #[inline]
+    pub fn colspan_header<S: AsRef<str>>(mut self, columns: usize, header: S) -> Self {
+        self.rows.push(InfoRow::Colspan(columns, cstring(header.as_ref())));
+        self
+    }
+
+    /// Prints the table, must be called from an info function
+    pub fn render(&self) {
+        unsafe {
+            zend::php_info_print_table_start();
+            for row in &self.rows {
+                match row {
+                    InfoRow::Header(cells) => print_cells(zend::php_info_print_table_header, cells),
+                    InfoRow::Row(cells) => print_cells(zend::php_info_print_table_row, cells),
+                    InfoRow::Colspan(columns, header) =>
+                        zend::php_info_print_table_colspan_header(*columns as c_int, header.as_ptr() as *mut _),
+                }
+            }
+            zend::php_info_print_table_end();
+        }
+    }
+}
+
+fn cstring(s: &str) -> CString {
+    CString::new(s.replace('\0', "")).unwrap_or_default()
+}
+
+fn cells_of<I, S>(cells: I) -> Vec<CString>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let cells: Vec<_> = cells.into_iter().map(|cell| cstring(cell.as_ref())).collect();
+    assert!(
+        !cells.is_empty() && cells.len() <= InfoTable::MAX_COLUMNS,
+        "phpinfo() table rows must have 1 to {} cells", InfoTable::MAX_COLUMNS
+    );
+    cells
+}
+
+/// Calls a variadic php_info_print_table_* function with the cells as arguments
+unsafe fn print_cells(print: unsafe extern "C" fn(c_int, ...), cells: &[CString]) {
+    let c: Vec<*const c_char> = cells.iter().map(|cell| cell.as_ptr()).collect();
+    match c.len() {
+        1 => print(1, c[0]),
+        2 => print(2, c[0], c[1]),
+        3 => print(3, c[0], c[1], c[2]),
+        4 => print(4, c[0], c[1], c[2], c[3]),
+        5 => print(5, c[0], c[1], c[2], c[3], c[4]),
+        6 => print(6, c[0], c[1], c[2], c[3], c[4], c[5]),
+        7 => print(7, c[0], c[1], c[2], c[3], c[4], c[5], c[6]),
+        8 => print(8, c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]),
+        _ => unreachable!(),
+    }
+}
@@ -0,0 +1,242 @@
+use std::{error, fmt, ptr, slice, str};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_uint, c_void};
+use crate::{zend, catch_panic, Long, Double, PanicContext};
+
+/// Value of an INI directive, parsed from its string form
+pub trait IniValue: Sized + 'static {
+    fn parse(value: &str) -> Option<Self>;
+
+    /// Displayer used by phpinfo(), the raw value is shown by default
+    const DISPLAYER: zend::zend_ini_disp = None;
+}
+
+/// "on", "yes" and "true" are true, other values by their leading integer like
+/// zend_ini_parse_bool: "1abc" is true, "off" and "foo" are false
+impl IniValue for bool {
+    fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if ["on", "yes", "true"].iter().any(|t| value.eq_ignore_ascii_case(t)) {
+            return Some(true);
+        }
+        // atoi() != 0
+        let digits = value.strip_prefix(&['+', '-'][..]).unwrap_or(value);
+        Some(digits.bytes().take_while(u8::is_ascii_digit).any(|d| d != b'0'))
+    }
+
+    const DISPLAYER: zend::zend_ini_disp = Some(zend::zend_ini_boolean_displayer_cb);
+}
+
+/// Integer with an optional K, M or G suffix, like zend_atol
+impl IniValue for Long {
+    fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (digits, multiplier) = match value.as_bytes().last()? {
+            b'k' | b'K' => (&value[..value.len() - 1], 1 << 10),
+            b'm' | b'M' => (&value[..value.len() - 1], 1 << 20),
+            b'g' | b'G' => (&value[..value.len() - 1], 1 << 30),
+            _ => (value, 1),
+        };
+        digits.parse::<Long>().ok()?.checked_mul(multiplier)
+    }
+}
+
+impl IniValue for Double {
+    fn parse(value: &str) -> Option<Self> {
+        value.trim().parse().ok()
+    }
+}
+
+impl IniValue for String {
+    fn parse(value: &str) -> Option<Self> {
+        Some(value.to_owned())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IniError {
+    /// Default value can't be parsed or is rejected by the validator
+    InvalidDefault { name: &'static str, default: &'static str },
+}
+
+impl fmt::Display for IniError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IniError::InvalidDefault { name, default } => {
+                write!(f, "Invalid default value '{}' of INI entry '{}'", default, name)
+            },
+        }
+    }
+}
+
+impl error::Error for IniError {}
+
+/// Typed INI directive, declared as a static and added with `Module::add_ini_entry`.
+/// Enum directives implement `IniValue` for their own type.
+///
+/// ```ignore
+/// static MAX_SIZE: IniEntry<Long> = IniEntry::new("rust_ext.max_size", "1M", PHP_INI_ALL);
+///
+/// module.add_ini_entry(&MAX_SIZE).expect("invalid INI default");
+/// let max_size = MAX_SIZE.get();
+/// ```
+pub struct IniEntry<T> {
+    name: &'static str,
+    default: &'static str,
+    modifiable: c_int,
+    validator: Option<fn(&T) -> bool>,
+}
+
+impl<T: IniValue> IniEntry<T> {
+    /// Directive modifiable in PHP_INI_USER, PHP_INI_PERDIR and/or PHP_INI_SYSTEM scope
+    pub const fn new(name: &'static str, default: &'static str, modifiable: c_int) -> Self {
+        IniEntry {
+            name,
+            default,
+            modifiable,
+            validator: None,
+        }
+    }
+
+    /// Values rejected by `validator` are not applied, in addition to the ones that can't be parsed
+    pub const fn with_validator(mut self, validator: fn(&T) -> bool) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Current value for this request, the default one before the entry is registered.
+    ///
+    /// # Panics
+    ///
+    /// If the entry was never added to the module and its default is invalid
+    pub fn get(&self) -> T {
+        self.value(false)
+    }
+
+    /// Value set in php.ini, ignoring `ini_set()` changes
+    pub fn get_orig(&self) -> T {
+        self.value(true)
+    }
+
+    fn value(&self, orig: bool) -> T {
+        let mut exists = 0;
+        let value = unsafe {
+            zend::zend_ini_string_ex(self.name.as_ptr() as *mut _, self.name.len() as c_uint, orig as c_int, &mut exists)
+        };
+        let value = if exists != 0 && !value.is_null() {
+            unsafe { str::from_utf8(CStr::from_ptr(value).to_bytes()).ok() }
+        } else {
+            None
+        };
+        value.and_then(T::parse)
+            .or_else(|| T::parse(self.default))
+            .expect("Invalid default value of INI entry")
+    }
+
+    /// Checks that the default value parses and passes the validator
+    pub fn validate(&self) -> Result<(), IniError> {
+        if self.accepts(self.default) {
+            Ok(())
+        } else {
+            Err(IniError::InvalidDefault { name: self.name, default: self.default })
+        }
+    }
+
+    fn accepts(&self, value: &str) -> bool {
+        match T::parse(value) {
+            Some(value) => match self.validator {
+                Some(validator) => validator(&value),
+                None => true,
+            },
+            None => false,
+        }
+    }
+
+    pub(crate) fn def(&'static self) -> IniEntryDef {
+        IniEntryDef(zend::zend_ini_entry_def {
+            name: self.name.as_ptr() as *const c_char,
+            on_modify: Some(on_modify::<T>),
+            mh_arg1: self as *const Self as *mut c_void,
+            mh_arg2: ptr::null_mut(),
+            mh_arg3: ptr::null_mut(),
+            value: self.default.as_ptr() as *const c_char,
+            displayer: T::DISPLAYER,
+            modifiable: self.modifiable,
+            name_length: self.name.len() as c_uint,
+            value_length: self.default.len() as c_uint,
+        })
+    }
+}
+
+/// Entry definition waiting for module startup, names and values are copied by the engine
+pub(crate) struct IniEntryDef(pub(crate) zend::zend_ini_entry_def);
+
+/// Points to the statics the definition is built from
+unsafe impl Send for IniEntryDef {}
+
+/// Rejects values that can't be parsed as `T` or fail validation
+unsafe extern "C" fn on_modify<T: IniValue>(
+    _entry: *mut zend::zend_ini_entry,
+    new_value: *mut zend::zend_string,
+    mh_arg1: *mut c_void,
+    _mh_arg2: *mut c_void,
+    _mh_arg3: *mut c_void,
+    _stage: c_int,
+) -> c_int {
+    let entry = &*(mh_arg1 as *const IniEntry<T>);
+    let value = match new_value.as_ref() {
+        Some(value) => str::from_utf8(slice::from_raw_parts(value.val.as_ptr(), value.len)).ok(),
+        None => Some(entry.default),
+    };
+    let accepted = value.is_some_and(|value| catch_panic(PanicContext::Request, || entry.accepts(value)) == Some(true));
+    if accepted {
+        zend::ZEND_RESULT_CODE::SUCCESS.as_int()
+    } else {
+        zend::ZEND_RESULT_CODE::FAILURE.as_int()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ini_values() {
+        assert_eq!(bool::parse("On"), Some(true));
+        assert_eq!(bool::parse("yes"), Some(true));
+        assert_eq!(bool::parse("2"), Some(true));
+        assert_eq!(bool::parse("off"), Some(false));
+        assert_eq!(bool::parse(""), Some(false));
+        assert_eq!(bool::parse("1abc"), Some(true));
+        assert_eq!(bool::parse("-1"), Some(true));
+        assert_eq!(bool::parse("00"), Some(false));
+        assert_eq!(bool::parse("maybe"), Some(false));
+
+        assert_eq!(Long::parse("42"), Some(42));
+        assert_eq!(Long::parse(" -3 "), Some(-3));
+        assert_eq!(Long::parse("128M"), Some(128 << 20));
+        assert_eq!(Long::parse("2k"), Some(2048));
+        assert_eq!(Long::parse("1G"), Some(1 << 30));
+        assert_eq!(Long::parse("ten"), None);
+        assert_eq!(Long::parse(""), None);
+
+        assert_eq!(Double::parse("0.5"), Some(0.5));
+        assert_eq!(Double::parse("x"), None);
+    }
+
+    #[test]
+    fn validate_defaults() {
+        assert!(IniEntry::<Long>::new("ext.size", "1M", 0).validate().is_ok());
+        assert_eq!(
+            IniEntry::<Long>::new("ext.size", "big", 0).validate(),
+            Err(IniError::InvalidDefault { name: "ext.size", default: "big" })
+        );
+        let positive = IniEntry::<Long>::new("ext.size", "-1", 0).with_validator(|value| *value > 0);
+        assert!(positive.validate().is_err());
+    }
+}
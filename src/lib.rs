@@ -1,8 +1,25 @@
 pub use libc;
+pub use php_rust_macros::{php_function, php_class};
+pub use class::*;
+pub use exception::*;
 pub use function::*;
+pub use globals::*;
+pub use info::*;
+pub use ini::*;
+pub use lifecycle::*;
+pub use object::*;
+pub use panic::*;
 pub use safe_api::*;
 
 pub mod zend;
 pub mod macros;
+pub mod class;
+pub mod exception;
 pub mod function;
+pub mod globals;
+pub mod info;
+pub mod ini;
+pub mod lifecycle;
+pub mod object;
+pub mod panic;
 pub mod safe_api;
\ No newline at end of file
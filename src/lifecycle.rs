@@ -0,0 +1,77 @@
+use std::error;
+use std::os::raw::c_int;
+
+/// Result of a lifecycle callback, an error makes the engine treat the phase as failed
+pub type LifecycleResult = Result<(), Box<dyn error::Error>>;
+
+/// How the module was loaded (MODULE_PERSISTENT or MODULE_TEMPORARY)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StartupType {
+    /// Loaded by `extension=` in php.ini, lives as long as the process
+    Persistent = 1,
+    /// Loaded by `dl()`, unloaded at the end of the request
+    Temporary = 2,
+}
+
+impl StartupType {
+    #[inline]
+    pub fn from_raw(type_: c_int) -> Self {
+        match type_ {
+            2 => StartupType::Temporary,
+            _ => StartupType::Persistent,
+        }
+    }
+}
+
+/// Passed to every lifecycle callback
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ModuleContext {
+    startup_type: StartupType,
+    module_number: c_int,
+}
+
+impl ModuleContext {
+    #[inline]
+    pub fn new(startup_type: StartupType, module_number: c_int) -> Self {
+        ModuleContext { startup_type, module_number }
+    }
+
+    #[inline]
+    pub fn startup_type(&self) -> StartupType {
+        self.startup_type
+    }
+
+    /// Module number assigned by the engine, used to register constants and INI entries
+    #[inline]
+    pub fn module_number(&self) -> c_int {
+        self.module_number
+    }
+}
+
+/// All lifecycle callbacks of an extension, registered at once with `Module::set_lifecycle`
+pub trait ExtensionLifecycle: Send + Sync + 'static {
+    /// MINIT, after the classes added to the module are registered
+    fn module_startup(&self, _ctx: &ModuleContext) -> LifecycleResult {
+        Ok(())
+    }
+
+    /// MSHUTDOWN
+    fn module_shutdown(&self, _ctx: &ModuleContext) -> LifecycleResult {
+        Ok(())
+    }
+
+    /// RINIT, before every request
+    fn request_startup(&self, _ctx: &ModuleContext) -> LifecycleResult {
+        Ok(())
+    }
+
+    /// RSHUTDOWN, after every request while the executor is still available
+    fn request_shutdown(&self, _ctx: &ModuleContext) -> LifecycleResult {
+        Ok(())
+    }
+
+    /// After every request, once the executor and output are shut down
+    fn post_deactivate(&self, _ctx: &ModuleContext) -> LifecycleResult {
+        Ok(())
+    }
+}
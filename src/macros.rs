@@ -6,15 +6,56 @@ macro_rules! c_str {
     }}
 }
 
+/// Function entries for `fn(&ExecuteData, &mut Zval)` handlers, each one is wrapped
+/// into an `extern fn` turning panics into PHP errors
 #[macro_export]
 macro_rules! funs {
     ($($fname:ident),*) => {
         Box::new(
-            [$($crate::Function::new($crate::c_str!(stringify!($fname)), $fname),)* $crate::Function::end()]
+            [$($crate::Function::new($crate::c_str!(stringify!($fname)), {
+                extern "C" fn handler(execute_data: &$crate::ExecuteData, retval: &mut $crate::Zval) {
+                    $crate::catch_panic($crate::PanicContext::Request, || $fname(execute_data, retval));
+                }
+                handler
+            }),)* $crate::Function::end()]
         )
     }
 }
 
+/// Method entry for a `fn(&ExecuteData, &mut Zval)` handler, wrapped like in `funs!`.
+/// Flags are set with `Function::with_flags`.
+#[macro_export]
+macro_rules! method {
+    ($name:expr, $handler:path) => {
+        $crate::Function::new($crate::c_str!($name), {
+            extern "C" fn handler(execute_data: &$crate::ExecuteData, retval: &mut $crate::Zval) {
+                $crate::catch_panic($crate::PanicContext::Request, || $handler(execute_data, retval));
+            }
+            handler
+        })
+    }
+}
+
+/// Function entries for handlers generated by `#[php_function]`
+#[macro_export]
+macro_rules! php_funs {
+    ($($fname:ident),*) => {
+        Box::new(
+            [$($crate::Function::new_with_args($crate::c_str!(stringify!($fname)), $fname, $fname::arg_info()),)* $crate::Function::end()]
+        )
+    }
+}
+
+/// Permanent interned `ZString` for a literal with a precomputed hash, allocated once per call site.
+/// Fast to look up repeatedly: `arr.get(zstr!("name"))`.
+#[macro_export]
+macro_rules! zstr {
+    ($s:literal) => {{
+        static INTERNED: $crate::InternedStr = $crate::InternedStr::new($s);
+        INTERNED.get()
+    }}
+}
+
 #[macro_export]
 macro_rules! str_index {
     ($s:expr) => {
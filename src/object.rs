@@ -0,0 +1,231 @@
+use std::{mem, ptr};
+use std::sync::atomic::{AtomicPtr, Ordering};
+use crate::{zend, catch_panic, ClassEntry, Zval, IntoZval, PanicContext, ToSafe};
+
+/// Rust type stored inside PHP objects, implemented by `#[php_class]`
+pub trait PhpClass: Sized + 'static {
+    /// PHP class name
+    const NAME: &'static str;
+
+    /// Slot the class entry and object handlers are stored to on registration
+    fn class_slot() -> &'static ClassSlot;
+
+    /// Registered class entry, `None` before module startup
+    #[inline]
+    fn class_entry() -> Option<&'static ClassEntry> {
+        Self::class_slot().class_entry()
+    }
+}
+
+/// Class entry and object handlers of a registered `PhpClass`
+pub struct ClassSlot {
+    ce: AtomicPtr<zend::ClassEntry>,
+    handlers: AtomicPtr<zend::ObjectHandlers>,
+}
+
+impl ClassSlot {
+    pub const fn new() -> Self {
+        ClassSlot {
+            ce: AtomicPtr::new(ptr::null_mut()),
+            handlers: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    #[inline]
+    pub fn class_entry(&self) -> Option<&'static ClassEntry> {
+        unsafe { self.ce.load(Ordering::Acquire).as_ref() }.map(|ce| ce.as_safe())
+    }
+
+    #[inline]
+    fn handlers(&self) -> *const zend::ObjectHandlers {
+        self.handlers.load(Ordering::Acquire)
+    }
+}
+
+impl Default for ClassSlot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Memory layout of objects of a `PhpClass`: the Rust value followed by the standard object,
+/// whose properties table extends past the end of the struct
+#[repr(C)]
+struct ZendClassObject<T> {
+    value: T,
+    std: zend::Object,
+}
+
+impl<T: PhpClass> ZendClassObject<T> {
+    const OFFSET: usize = mem::offset_of!(ZendClassObject<T>, std);
+
+    /// The Zend MM only aligns allocations to 8 bytes, checked when the hooks are built
+    const ALIGNED: () = assert!(mem::align_of::<Self>() <= 8, "PhpClass alignment is too large for the Zend MM");
+
+    #[inline]
+    unsafe fn from_std(object: *mut zend::Object) -> *mut Self {
+        (object as *mut u8).sub(Self::OFFSET) as *mut Self
+    }
+
+    /// Allocates and initializes the object the same way zend_objects_new does
+    unsafe fn alloc(ce: *mut zend::ClassEntry, value: T) -> *mut zend::Object {
+        let size = mem::size_of::<Self>().wrapping_add(zend::zend_object_properties_size(ce));
+        let object: *mut Self = zend::ecalloc(1, size);
+        ptr::write(&mut (*object).value, value);
+
+        let std = &mut (*object).std as *mut zend::Object;
+        zend::zend_object_std_init(std, ce);
+        zend::object_properties_init(std, ce);
+        (*std).handlers = T::class_slot().handlers();
+        std
+    }
+}
+
+/// Hooks installed into the class entry by `ClassBuilder::object`
+pub(crate) struct ObjectHooks {
+    create_object: unsafe extern "C" fn(*mut zend::ClassEntry) -> *mut zend::Object,
+    free_obj: unsafe extern "C" fn(*mut zend::Object),
+    clone_obj: zend::zend_object_clone_obj_t,
+    offset: usize,
+    slot: &'static ClassSlot,
+}
+
+impl ObjectHooks {
+    pub(crate) fn new<T: PhpClass + Default>() -> Self {
+        let () = ZendClassObject::<T>::ALIGNED;
+        ObjectHooks {
+            create_object: create_object::<T>,
+            free_obj: free_object::<T>,
+            clone_obj: None,
+            offset: ZendClassObject::<T>::OFFSET,
+            slot: T::class_slot(),
+        }
+    }
+
+    pub(crate) fn cloneable<T: PhpClass + Default + Clone>() -> Self {
+        ObjectHooks {
+            clone_obj: Some(clone_object::<T>),
+            ..Self::new::<T>()
+        }
+    }
+
+    /// Installs the hooks into a freshly registered class
+    pub(crate) unsafe fn install(&self, ce: *mut zend::ClassEntry) {
+        let mut handlers = zend::std_object_handlers;
+        handlers.offset = self.offset as _;
+        handlers.free_obj = Some(self.free_obj);
+        handlers.clone_obj = self.clone_obj;
+
+        self.slot.handlers.store(Box::into_raw(Box::new(handlers)), Ordering::Release);
+        self.slot.ce.store(ce, Ordering::Release);
+        (*ce).create_object = Some(self.create_object);
+    }
+}
+
+/// If `T::default()` panics a plain object without the Rust value is created instead,
+/// so the engine always gets an object and downcasts of it fail
+unsafe extern "C" fn create_object<T: PhpClass + Default>(ce: *mut zend::ClassEntry) -> *mut zend::Object {
+    match catch_panic(PanicContext::Request, T::default) {
+        Some(value) => ZendClassObject::<T>::alloc(ce, value),
+        None => zend::zend_objects_new(ce),
+    }
+}
+
+/// Drops the Rust value, the engine frees the memory afterwards using the handlers offset
+unsafe extern "C" fn free_object<T: PhpClass>(object: *mut zend::Object) {
+    let holder = ZendClassObject::<T>::from_std(object);
+    catch_panic(PanicContext::Request, || ptr::drop_in_place(&mut (*holder).value));
+    zend::zend_object_std_dtor(object);
+}
+
+unsafe extern "C" fn clone_object<T: PhpClass + Clone>(object: zend::zend_object_handler_obj) -> *mut zend::Object {
+    #[cfg(not(feature = "php80"))]
+    let old = (*object).value.obj;
+    #[cfg(feature = "php80")]
+    let old = object;
+    let value = match catch_panic(PanicContext::Request, || (*ZendClassObject::<T>::from_std(old)).value.clone()) {
+        Some(value) => value,
+        None => return zend::zend_objects_clone_obj(object),
+    };
+    let new = ZendClassObject::<T>::alloc((*old).ce, value);
+    zend::zend_objects_clone_members(new, old);
+    new
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Object(*mut zend::Object);
+
+impl Object {
+    /// New instance of a registered `PhpClass` holding `value`, the constructor is not called
+    pub fn new<T: PhpClass>(value: T) -> Option<Object> {
+        let ce = T::class_entry()?;
+        Some(Object(unsafe { ZendClassObject::alloc(ce.as_raw() as *mut _, value) }))
+    }
+
+    #[inline]
+    pub fn class(&self) -> &ClassEntry {
+        unsafe { (*self.0).ce.as_ref() }
+            .expect("Object has no class entry")
+            .as_safe()
+    }
+
+    #[inline]
+    pub fn handle(&self) -> u32 {
+        unsafe { (*self.0).handle }
+    }
+
+    /// Whether the object holds a `T`, i.e. was created by a `PhpClass` or a class extending it
+    #[inline]
+    pub fn is<T: PhpClass>(&self) -> bool {
+        let handlers = T::class_slot().handlers();
+        !handlers.is_null() && ptr::eq(unsafe { (*self.0).handlers }, handlers)
+    }
+
+    #[inline]
+    pub fn downcast_ref<T: PhpClass>(&self) -> Option<&T> {
+        if self.is::<T>() {
+            Some(unsafe { &(*ZendClassObject::<T>::from_std(self.0)).value })
+        } else {
+            None
+        }
+    }
+
+    /// Mutable Rust value of the object. `Object` is a copyable handle, prefer
+    /// `Zval::downcast_mut`, which borrows the zval holding the object.
+    ///
+    /// # Safety
+    /// No other reference to the value, obtained from this or another handle of the same
+    /// object, may be alive while the returned one is used
+    #[inline]
+    pub unsafe fn downcast_mut<T: PhpClass>(&mut self) -> Option<&mut T> {
+        if self.is::<T>() {
+            Some(&mut (*ZendClassObject::<T>::from_std(self.0)).value)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn raw(&self) -> *mut zend::Object {
+        self.0
+    }
+}
+
+impl ToSafe for *mut zend::Object {
+    type SafeType = Object;
+
+    #[inline]
+    fn into_safe(self) -> Self::SafeType {
+        Object(self)
+    }
+}
+
+impl IntoZval for Object {
+    #[inline]
+    fn into_zval(self) -> Zval {
+        let mut zv = zend::Zval::default();
+        zv.set_obj(unsafe { &mut *self.0 });
+        zv.into_safe()
+    }
+}
@@ -0,0 +1,88 @@
+use std::{fmt, panic};
+use std::any::Any;
+use std::ffi::CString;
+use std::panic::AssertUnwindSafe;
+use std::sync::{RwLock, PoisonError};
+use crate::{c_str, zend, PhpException};
+
+/// Where a panic was caught
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PanicContext {
+    /// Module startup, the extension can't be used after it
+    Startup,
+    /// Module shutdown
+    Shutdown,
+    /// Function handlers, object handlers and other callbacks running during a request
+    Request,
+}
+
+/// Panic caught at an FFI boundary
+#[derive(Debug, Clone)]
+pub struct Panic {
+    message: String,
+    context: PanicContext,
+}
+
+impl Panic {
+    fn from_payload(payload: &(dyn Any + Send), context: PanicContext) -> Self {
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            (*message).to_owned()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "Box<dyn Any>".to_owned()
+        };
+        Panic { message, context }
+    }
+
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    #[inline]
+    pub fn context(&self) -> PanicContext {
+        self.context
+    }
+}
+
+impl fmt::Display for Panic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Rust panic: {}", self.message)
+    }
+}
+
+/// Called with every panic caught at an FFI boundary, must not panic itself
+pub type PanicHandler = fn(&Panic);
+
+static PANIC_HANDLER: RwLock<PanicHandler> = RwLock::new(default_panic_handler);
+
+/// Replaces the handler converting panics to PHP errors
+pub fn set_panic_handler(handler: PanicHandler) {
+    *PANIC_HANDLER.write().unwrap_or_else(PoisonError::into_inner) = handler;
+}
+
+/// Throws `Error` during requests, raises a fatal error during startup
+/// and a warning during shutdown
+pub fn default_panic_handler(panic: &Panic) {
+    let level = match panic.context {
+        PanicContext::Request => return PhpException::error(panic.to_string()).throw(),
+        PanicContext::Startup => zend::E_CORE_ERROR,
+        PanicContext::Shutdown => zend::E_CORE_WARNING,
+    };
+    let message = CString::new(panic.to_string().replace('\0', "\\0")).unwrap_or_default();
+    unsafe { zend::zend_error(level, c_str!("%s"), message.as_ptr()) };
+}
+
+/// Runs `f`, a panic is passed to the panic handler instead of unwinding into the engine
+#[doc(hidden)]
+pub fn catch_panic<R, F: FnOnce() -> R>(context: PanicContext, f: F) -> Option<R> {
+    let panic = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => return Some(result),
+        Err(payload) => Panic::from_payload(&*payload, context),
+    };
+    let handler = *PANIC_HANDLER.read().unwrap_or_else(PoisonError::into_inner);
+    handler(&panic);
+    None
+}
+
@@ -3,6 +3,7 @@ use std::os::raw::c_void;
 use crate::zend;
 
 pub use array::*;
+pub use convert::*;
 pub use execute_data::*;
 pub use refcounted::*;
 pub use string::*;
@@ -17,6 +18,7 @@ pub use zend::Uchar;
 pub use zend::Type;
 
 pub mod array;
+pub mod convert;
 pub mod execute_data;
 pub mod refcounted;
 pub mod string;
@@ -1,9 +1,9 @@
 use std::{
-    ops::{Deref, DerefMut, Index},
-    slice::from_raw_parts_mut, os::raw::c_char,
+    cell::Cell, cmp::Ordering, collections::HashMap, convert::TryFrom, marker::PhantomData, mem, ops::{Deref, DerefMut, Index}, ptr::{self, NonNull},
+    slice::{self, from_raw_parts_mut}, os::raw::{c_char, c_int, c_void},
 };
 use libc::strlen;
-use crate::{zend, ToSafe, Zval, ZString, Ulong};
+use crate::{zend, catch_panic, ToSafe, Zval, OwnedZval, ZString, Long, Ulong, IntoZval, PanicContext};
 
 #[repr(C)]
 #[derive(Copy, Clone)]
@@ -22,17 +22,41 @@ impl Bucket {
             .map(|z| z.as_safe_mut())
     }
 
+    /// Integer key, `None` for string keys
     #[inline]
     pub fn get_hash(&self) -> Option<Ulong> {
-        Some(self.0.h)
+        if self.0.key.is_null() {
+            Some(self.0.h)
+        } else {
+            None
+        }
     }
 
     #[inline]
     pub fn get_key(&self) -> Option<ZString> {
-        Some(self.0.key.into_safe())
+        if self.0.key.is_null() {
+            None
+        } else {
+            Some(self.0.key.into_safe())
+        }
+    }
+
+    #[inline]
+    pub fn key(&self) -> ArrayKey {
+        match self.get_key() {
+            Some(key) => ArrayKey::Str(key),
+            None => ArrayKey::Int(self.0.h),
+        }
     }
 }
 
+/// Key of an array element
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ArrayKey {
+    Int(Ulong),
+    Str(ZString),
+}
+
 impl ToSafe for zend::Bucket {
     type SafeType = Bucket;
 }
@@ -45,6 +69,48 @@ pub enum ArrayIndex<'a> {
     NtStr(&'a str),
     /// C-string with its length
     Cstr(*const c_char, usize),
+    /// Integer key, negative keys are stored as their two's complement
+    Int(Ulong),
+}
+
+impl ArrayIndex<'_> {
+    /// Symbol table semantics: strings in canonical decimal form, like `"42"`, are the same
+    /// keys as the integers they represent
+    pub fn to_symtable(self) -> Self {
+        let key = unsafe {
+            match self {
+                ArrayIndex::ZString(zs) => (*zs).as_bytes(),
+                ArrayIndex::NtStr(nts) => &nts.as_bytes()[..nts.len() - 1],
+                ArrayIndex::Cstr(cs, len) => slice::from_raw_parts(cs as *const u8, len),
+                ArrayIndex::Int(_) => return self,
+            }
+        };
+        match zend::handle_numeric_str(key) {
+            Some(h) => ArrayIndex::Int(h),
+            None => self,
+        }
+    }
+}
+
+impl From<ArrayKey> for ArrayIndex<'_> {
+    fn from(from: ArrayKey) -> Self {
+        match from {
+            ArrayKey::Int(h) => ArrayIndex::Int(h),
+            ArrayKey::Str(key) => ArrayIndex::ZString(key.into()),
+        }
+    }
+}
+
+impl From<Ulong> for ArrayIndex<'_> {
+    fn from(from: Ulong) -> Self {
+        ArrayIndex::Int(from)
+    }
+}
+
+impl From<Long> for ArrayIndex<'_> {
+    fn from(from: Long) -> Self {
+        ArrayIndex::Int(from as Ulong)
+    }
 }
 
 impl From<ZString> for ArrayIndex<'_> {
@@ -102,70 +168,51 @@ impl ArrayBuilder {
         self
     }
 
+    #[track_caller]
     #[inline]
-    pub fn build(self) -> Array {
+    pub fn build(self) -> ZArray {
         let ArrayBuilder { initial_min_size, value_destructor, persistent } = self;
-        Array::init(initial_min_size, value_destructor, persistent)
+        let ht = zend::Array::alloc();
+        zend::Array::init(ht, initial_min_size, (), value_destructor, persistent);
+        unsafe { ZArray::from_raw(ht) }
     }
 }
 
-#[repr(C)]
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub struct Array(*mut zend::Array);
-
+/// Array operations, implemented for everything that can be viewed as a `ZArrayRef`
 pub trait ArrayApi {
-    fn get<'a, I>(&self, index: I) -> Option<&Zval>
-    where
-        I: Into<ArrayIndex<'a>>;
+    fn as_array_ref(&self) -> &ZArrayRef;
 
-    fn insert<'a, I, V>(&mut self, index: I, value: V) -> Option<&Zval>
-    where
-        I: Into<ArrayIndex<'a>>,
-        V: Into<Zval>;
+    /// Separates shared arrays before handing them out for modification
+    fn as_array_mut(&mut self) -> &mut ZArrayRef;
 
-    fn exists<'a, I>(&self, index: I) -> bool
-    where
-        I: Into<ArrayIndex<'a>>;
-
-    fn buckets_iter(&self) -> BucketsIter;
-
-    fn buckets_iter_mut(&mut self) -> BucketsIterMut;
-}
-
-impl Array {
+    /// Numeric string keys are looked up as integer keys
     #[inline]
-    pub fn new() -> Self {
-        let ArrayBuilder { initial_min_size, value_destructor, persistent } = ArrayBuilder::new();
-        Self::init(initial_min_size, value_destructor, persistent)
-    }
-
-    #[inline]
-    pub fn init(size: u32, destructor: zend::DtorFuncT, persistent: bool) -> Self {
-        let ht = zend::Array::alloc();
-        zend::Array::init(ht, size, (), destructor, persistent);
-        Array(ht)
-    }
-
-    #[inline]
-    pub fn raw(&self) -> *mut zend::Array {
-        self.0
+    fn get<'a, I>(&self, index: I) -> Option<&Zval>
+    where
+        I: Into<ArrayIndex<'a>>
+    {
+        unsafe { find(self.as_array_ref().as_raw(), index.into()).map(|zv| &*zv) }
     }
-}
 
-impl ArrayApi for Array {
+    /// Adds or replaces the value, numeric string keys are stored as integer keys
+    #[track_caller]
     #[inline]
-    fn get<'a, I>(&self, index: I) -> Option<&Zval>
+    fn insert<'a, I, V>(&mut self, index: I, val: V) -> Option<&Zval>
     where
-        I: Into<ArrayIndex<'a>>
+        I: Into<ArrayIndex<'a>>,
+        V: IntoZval,
     {
+        let ht = self.as_array_mut().as_raw();
         unsafe {
-            let zv = match index.into() {
+            let zv = match index.into().to_symtable() {
                 ArrayIndex::ZString(zs) =>
-                    zend::HashTable::find_ind(self.0, zs),
+                    zend::HashTable::update_ind(ht, zs, val.into_zval().as_raw_mut()),
                 ArrayIndex::NtStr(nts) =>
-                    zend::HashTable::str_find_ind(self.0, nts.as_ptr() as *const _, nts.len() - 1),
+                    zend::HashTable::str_update_ind(ht, nts.as_ptr() as *const _, nts.len() - 1, val.into_zval().as_raw_mut()),
                 ArrayIndex::Cstr(cs, len) =>
-                    zend::HashTable::str_find_ind(self.0, cs, len),
+                    zend::HashTable::str_update_ind(ht, cs, len, val.into_zval().as_raw_mut()),
+                ArrayIndex::Int(h) =>
+                    zend::HashTable::index_update(ht, h, val.into_zval().as_raw_mut()),
             };
             if zv.is_null() {
                 None
@@ -175,22 +222,51 @@ impl ArrayApi for Array {
         }
     }
 
+    /// Adds the value only if the key doesn't exist yet, `None` otherwise
+    #[track_caller]
     #[inline]
-    fn insert<'a, I, V>(&mut self, index: I, val: V) -> Option<&Zval>
+    fn insert_new<'a, I, V>(&mut self, index: I, val: V) -> Option<&Zval>
     where
         I: Into<ArrayIndex<'a>>,
-        V: Into<Zval>,
+        V: IntoZval,
     {
+        let index = index.into().to_symtable();
+        if self.exists(index) {
+            return None;
+        }
+        let ht = self.as_array_mut().as_raw();
         unsafe {
-            let zv = match index.into() {
+            let mut val = val.into_zval();
+            let zv = match index {
                 ArrayIndex::ZString(zs) =>
-                    zend::HashTable::update_ind(self.0, zs, val.into().as_raw_mut()),
+                    zend::HashTable::add(ht, zs, val.as_raw_mut()),
                 ArrayIndex::NtStr(nts) =>
-                    zend::HashTable::str_update_ind(self.0, nts.as_ptr() as *const _, nts.len() - 1, val.into().as_raw_mut()),
+                    zend::HashTable::str_add(ht, nts.as_ptr() as *const _, nts.len() - 1, val.as_raw_mut()),
                 ArrayIndex::Cstr(cs, len) =>
-                    zend::HashTable::str_update_ind(self.0, cs, len, val.into().as_raw_mut()),
+                    zend::HashTable::str_add(ht, cs, len, val.as_raw_mut()),
+                ArrayIndex::Int(h) =>
+                    zend::HashTable::index_add(ht, h, val.as_raw_mut()),
             };
             if zv.is_null() {
+                zend::Zval::ptr_dtor(val.as_raw_mut());
+                None
+            } else {
+                Some(&*(zv as *const Zval))
+            }
+        }
+    }
+
+    /// Appends the value with the next free integer key, like `$arr[] = $val`.
+    /// `None` if the next key would overflow.
+    #[track_caller]
+    #[inline]
+    fn push<V: IntoZval>(&mut self, val: V) -> Option<&Zval> {
+        let ht = self.as_array_mut().as_raw();
+        unsafe {
+            let mut val = val.into_zval();
+            let zv = zend::HashTable::next_index_insert(ht, val.as_raw_mut());
+            if zv.is_null() {
+                zend::Zval::ptr_dtor(val.as_raw_mut());
                 None
             } else {
                 Some(&*(zv as *const Zval))
@@ -203,27 +279,693 @@ impl ArrayApi for Array {
     where
         I: Into<ArrayIndex<'a>>
     {
+        let ht = self.as_array_ref().as_raw();
         unsafe {
-            match index.into() {
+            match index.into().to_symtable() {
                 ArrayIndex::ZString(zs) =>
-                    zend::HashTable::exists_ind(self.0, zs),
+                    zend::HashTable::exists_ind(ht, zs),
                 ArrayIndex::NtStr(nts) =>
-                    zend::HashTable::str_exists_ind(self.0, nts.as_ptr() as *const _, nts.len() - 1),
+                    zend::HashTable::str_exists_ind(ht, nts.as_ptr() as *const _, nts.len() - 1),
                 ArrayIndex::Cstr(cs, len) =>
-                    zend::HashTable::str_exists_ind(self.0, cs, len),
+                    zend::HashTable::str_exists_ind(ht, cs, len),
+                ArrayIndex::Int(h) =>
+                    zend::HashTable::index_exists(ht, h),
+            }
+        }
+    }
+
+    #[inline]
+    fn get_mut<'a, I>(&mut self, index: I) -> Option<&mut Zval>
+    where
+        I: Into<ArrayIndex<'a>>
+    {
+        unsafe { find(self.as_array_mut().as_raw(), index.into()).map(|zv| &mut *zv) }
+    }
+
+    /// Takes the value out of the array, it's released when the returned zval is dropped
+    fn remove<'a, I>(&mut self, index: I) -> Option<OwnedZval>
+    where
+        I: Into<ArrayIndex<'a>>
+    {
+        let index = index.into().to_symtable();
+        let zv = self.get_mut(index)?;
+        let val = unsafe { OwnedZval::new(*zv) };
+        // the destructor called on deletion must not release the taken value
+        zv.set_null();
+        let ht = self.as_array_mut().as_raw();
+        unsafe {
+            match index {
+                ArrayIndex::ZString(zs) => zend::HashTable::del_ind(ht, zs),
+                ArrayIndex::NtStr(nts) => zend::HashTable::str_del_ind(ht, nts.as_ptr() as *const _, nts.len() - 1),
+                ArrayIndex::Cstr(cs, len) => zend::HashTable::str_del_ind(ht, cs, len),
+                ArrayIndex::Int(h) => zend::HashTable::index_del(ht, h),
+            };
+        }
+        Some(val)
+    }
+
+    /// Keeps only the elements for which `f` returns `true`, in place
+    fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(ArrayKey, &mut Zval) -> bool
+    {
+        let ht = self.as_array_mut().as_raw();
+        unsafe {
+            let mut idx = 0;
+            while idx < (*ht).nNumUsed as usize {
+                if let Some((key, zv)) = slot(ht, idx) {
+                    if !f(key, (*zv).as_safe_mut()) {
+                        if cfg!(feature = "php82") && (*ht).is_packed() {
+                            zend::HashTable::index_del(ht, idx as Ulong);
+                        } else {
+                            zend::HashTable::del_bucket_ind(ht, (*ht).arData.add(idx));
+                        }
+                    }
+                }
+                idx += 1;
             }
         }
     }
 
+    /// zend_hash_clean: removes all elements, keeping the allocated buckets
     #[inline]
-    fn buckets_iter(&self) -> BucketsIter {
-        unsafe { BucketsIter::from_raw(self.0) }
+    fn clear(&mut self) {
+        zend::HashTable::clean(self.as_array_mut().as_raw());
     }
 
+    /// In-place access to an element that may not exist yet, like `HashMap::entry`
     #[inline]
-    fn buckets_iter_mut(&mut self) -> BucketsIterMut {
-        unsafe { BucketsIterMut::from_raw(self.0) }
+    fn entry<'a, I>(&'a mut self, index: I) -> Entry<'a>
+    where
+        I: Into<ArrayIndex<'a>>
+    {
+        Entry {
+            array: self.as_array_mut(),
+            index: index.into().to_symtable(),
+        }
     }
+
+    /// Buckets in order, `None` for packed arrays since PHP 8.2: they store zvals, use `iter`
+    #[inline]
+    fn buckets_iter(&self) -> Option<BucketsIter<'_>> {
+        let array = self.as_array_ref();
+        if cfg!(feature = "php82") && array.is_packed() {
+            None
+        } else {
+            Some(unsafe { BucketsIter::from_raw(array.as_raw()) })
+        }
+    }
+
+    /// Mutable `buckets_iter`, `None` for packed arrays since PHP 8.2
+    #[inline]
+    fn buckets_iter_mut(&mut self) -> Option<BucketsIterMut<'_>> {
+        let array = self.as_array_mut();
+        if cfg!(feature = "php82") && array.is_packed() {
+            None
+        } else {
+            Some(unsafe { BucketsIterMut::from_raw(array.as_raw()) })
+        }
+    }
+
+    /// Sorts the values with PHP comparison, stable. Keys are renumbered like `sort()`,
+    /// or kept like `asort()`.
+    fn sort(&mut self, renumber: bool) {
+        sort_buckets(self.as_array_mut(), renumber, |a, b| match (a.get_val(), b.get_val()) {
+            (Some(a), Some(b)) => a.compare(b),
+            _ => Ordering::Equal,
+        });
+    }
+
+    /// Stable sort with a comparator, keys are renumbered like `usort()`
+    fn sort_by<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Zval, &Zval) -> Ordering
+    {
+        sort_buckets(self.as_array_mut(), true, |a, b| match (a.get_val(), b.get_val()) {
+            (Some(a), Some(b)) => f(a, b),
+            _ => Ordering::Equal,
+        });
+    }
+
+    /// Stable sort by a key extracted from every value, keys are renumbered like `usort()`
+    #[inline]
+    fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        K: Ord,
+        F: FnMut(&Zval) -> K
+    {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    /// Sorts by key with PHP comparison, like `ksort()`
+    fn ksort(&mut self) {
+        sort_buckets(self.as_array_mut(), false, |a, b| {
+            let (mut a, mut b) = (key_zval(a.key()), key_zval(b.key()));
+            unsafe { zend::Zval::compare(&mut a, &mut b).cmp(&0) }
+        });
+    }
+
+    /// Adds the elements of `other` like `array_merge()`: string keys are overwritten,
+    /// integer keys are appended
+    #[inline]
+    fn merge(&mut self, other: &ZArrayRef) {
+        unsafe { zend::HashTable::array_merge(self.as_array_mut().as_raw(), other.as_raw()) };
+    }
+
+    /// Adds the elements of `other` like `array_replace()`: all existing keys are overwritten
+    #[track_caller]
+    #[inline]
+    fn replace(&mut self, other: &ZArrayRef) {
+        unsafe { zend::HashTable::merge(self.as_array_mut().as_raw(), other.as_raw(), Some(zend::ZVAL_ADD_REF), true) };
+    }
+
+    /// `ZArrayRef::compare`, named apart from `Zval::compare` which compares any values
+    #[inline]
+    fn compare_array(&self, other: &ZArrayRef, ordered: bool) -> Ordering {
+        self.as_array_ref().compare(other, ordered)
+    }
+
+    /// Elements with their keys in array order, reversible like zend_hash_move_backwards_ex
+    #[inline]
+    fn iter(&self) -> Iter<'_> {
+        unsafe { Iter::from_raw(self.as_array_ref().as_raw()) }
+    }
+
+    #[inline]
+    fn iter_mut(&mut self) -> IterMut<'_> {
+        unsafe { IterMut::from_raw(self.as_array_mut().as_raw()) }
+    }
+
+    #[inline]
+    fn keys(&self) -> Keys<'_> {
+        Keys(self.iter())
+    }
+
+    #[inline]
+    fn values(&self) -> Values<'_> {
+        Values(self.iter())
+    }
+}
+
+thread_local! {
+    /// Comparator of the innermost running `sort_buckets`, zend_sort has no user data argument
+    static SORT_COMPARE: Cell<*mut c_void> = const { Cell::new(ptr::null_mut()) };
+}
+
+type BucketCompare<'a> = &'a mut dyn FnMut(&Bucket, &Bucket) -> Ordering;
+
+/// zend_hash_sort_ex with a Rust comparator. Equal elements keep their order: the positions
+/// are looked up by key in a side buffer, the `u2` of the values is the hash chain before PHP 8.0.
+fn sort_buckets<F: FnMut(&Bucket, &Bucket) -> Ordering>(array: &mut ZArrayRef, renumber: bool, mut f: F) {
+    let ht = array.as_raw();
+    unsafe {
+        if (*ht).nNumOfElements <= 1 && !renumber {
+            return;
+        }
+        let order: HashMap<_, u32> = Iter::from_raw(ht).map(|(key, _)| key_id(key)).zip(0..).collect();
+        // UNDEF indirect elements are not iterated, they go last
+        let position = |b: &Bucket| order.get(&key_id(b.key())).copied().unwrap_or(u32::MAX);
+        let mut stable = |a: &Bucket, b: &Bucket| f(a, b).then_with(|| position(a).cmp(&position(b)));
+        let mut cmp: BucketCompare<'_> = &mut stable;
+        let prev = SORT_COMPARE.with(|c| c.replace(&mut cmp as *mut BucketCompare<'_> as *mut c_void));
+        zend::HashTable::sort_ex(ht, zend::zend_sort, compare_buckets, renumber);
+        SORT_COMPARE.with(|c| c.set(prev));
+    }
+}
+
+extern "C" fn compare_buckets(a: *const c_void, b: *const c_void) -> c_int {
+    let cmp = SORT_COMPARE.with(Cell::get) as *mut BucketCompare<'_>;
+    let (a, b) = unsafe { (&*(a as *const zend::Bucket), &*(b as *const zend::Bucket)) };
+    catch_panic(PanicContext::Request, || unsafe { (*cmp)(a.as_safe(), b.as_safe()) })
+        .unwrap_or(Ordering::Equal) as c_int
+}
+
+/// Identity of an element that survives the moves of the sort, keys are unique
+#[inline]
+fn key_id(key: ArrayKey) -> (usize, Ulong) {
+    match key {
+        ArrayKey::Int(h) => (0, h),
+        ArrayKey::Str(s) => (<*mut zend::String>::from(s) as usize, 0),
+    }
+}
+
+extern "C" fn compare_zvals(a: *const c_void, b: *const c_void) -> c_int {
+    unsafe { zend::Zval::compare(a as *mut _, b as *mut _) }
+}
+
+/// Temporary zval for comparing keys, it doesn't own the key string
+fn key_zval(key: ArrayKey) -> zend::Zval {
+    let mut zv = zend::Zval::default();
+    match key {
+        ArrayKey::Int(h) => zv.set_long(h as Long),
+        ArrayKey::Str(s) => {
+            let s: *mut zend::String = s.into();
+            zv.set_str(unsafe { &mut *s })
+        }
+    }
+    zv
+}
+
+/// Key and value of the slot at `idx`, `None` for holes. Since PHP 8.2 packed arrays store
+/// bare zvals keyed by their position instead of buckets.
+#[inline]
+unsafe fn slot(ht: *const zend::Array, idx: usize) -> Option<(ArrayKey, *mut zend::Zval)> {
+    #[cfg(feature = "php82")]
+    if (*ht).is_packed() {
+        let zv = ((*ht).arData as *mut zend::Zval).add(idx);
+        return if (*zv).is_undef() { None } else { Some((ArrayKey::Int(idx as Ulong), zv)) };
+    }
+    let bucket = &*(*ht).arData.add(idx);
+    bucket.get_val(true).map(|zv| (bucket.as_safe().key(), zv as *const _ as *mut zend::Zval))
+}
+
+/// Value of the element with symtable key semantics, `None` if it doesn't exist
+unsafe fn find(ht: *mut zend::Array, index: ArrayIndex<'_>) -> Option<*mut Zval> {
+    let zv = match index.to_symtable() {
+        ArrayIndex::ZString(zs) =>
+            zend::HashTable::find_ind(ht, zs),
+        ArrayIndex::NtStr(nts) =>
+            zend::HashTable::str_find_ind(ht, nts.as_ptr() as *const _, nts.len() - 1),
+        ArrayIndex::Cstr(cs, len) =>
+            zend::HashTable::str_find_ind(ht, cs, len),
+        ArrayIndex::Int(h) =>
+            zend::HashTable::index_find(ht, h),
+    };
+    if zv.is_null() {
+        None
+    } else {
+        Some(zv as *mut Zval)
+    }
+}
+
+/// Element of a separated array, see `ArrayApi::entry`
+pub struct Entry<'a> {
+    array: &'a mut ZArrayRef,
+    index: ArrayIndex<'a>,
+}
+
+impl<'a> Entry<'a> {
+    #[inline]
+    pub fn index(&self) -> ArrayIndex<'a> {
+        self.index
+    }
+
+    #[track_caller]
+    #[inline]
+    pub fn or_insert<V: IntoZval>(self, default: V) -> &'a mut Zval {
+        self.or_insert_with(|| default)
+    }
+
+    /// Value of the element, `f` is called to create it only if it doesn't exist
+    #[track_caller]
+    pub fn or_insert_with<V: IntoZval, F: FnOnce() -> V>(self, f: F) -> &'a mut Zval {
+        let Entry { array, index } = self;
+        unsafe {
+            match find(array.as_raw(), index) {
+                Some(zv) => &mut *zv,
+                None => {
+                    array.insert(index, f());
+                    &mut *find(array.as_raw(), index).expect("Can't insert value into array")
+                }
+            }
+        }
+    }
+
+    /// Calls `f` with the existing value
+    #[inline]
+    pub fn and_modify<F: FnOnce(&mut Zval)>(self, f: F) -> Self {
+        if let Some(zv) = self.array.get_mut(self.index) {
+            f(zv);
+        }
+        self
+    }
+}
+
+#[cfg(feature = "php82")]
+type PackedSlot = zend::Zval;
+#[cfg(not(feature = "php82"))]
+type PackedSlot = zend::Bucket;
+
+#[inline]
+fn packed_slot_val(slot: &PackedSlot) -> Option<&Zval> {
+    #[cfg(feature = "php82")]
+    let zv = if slot.is_undef() { None } else { Some(slot) };
+    #[cfg(not(feature = "php82"))]
+    let zv = unsafe { slot.get_val(false) };
+    zv.map(|zv| zv.as_safe())
+}
+
+/// Values of a packed array by position, see `ZArrayRef::as_packed`
+#[derive(Copy, Clone)]
+pub struct PackedSlice<'a> {
+    slots: &'a [PackedSlot],
+}
+
+impl<'a> PackedSlice<'a> {
+    /// Number of slots, including holes left by removed elements
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Value at the position, `None` for holes
+    #[inline]
+    pub fn get(&self, idx: usize) -> Option<&'a Zval> {
+        self.slots.get(idx).and_then(packed_slot_val)
+    }
+
+    /// Values in order, holes are skipped
+    #[inline]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &'a Zval> + 'a {
+        self.slots.iter().filter_map(packed_slot_val)
+    }
+}
+
+/// Borrowed PHP array, `&ZArrayRef` for arrays received as arguments and `&mut ZArrayRef`
+/// for separated ones
+#[repr(transparent)]
+pub struct ZArrayRef(zend::Array);
+
+impl ZArrayRef {
+    /// # Safety
+    /// `ht` must point to a valid array that outlives `'a`
+    #[inline]
+    pub unsafe fn from_ptr<'a>(ht: *const zend::Array) -> &'a ZArrayRef {
+        &*(ht as *const ZArrayRef)
+    }
+
+    /// # Safety
+    /// `ht` must point to a valid array that outlives `'a`, not shared with other references
+    #[inline]
+    pub unsafe fn from_ptr_mut<'a>(ht: *mut zend::Array) -> &'a mut ZArrayRef {
+        &mut *(ht as *mut ZArrayRef)
+    }
+
+    #[inline]
+    pub fn as_raw(&self) -> *mut zend::Array {
+        &self.0 as *const _ as *mut _
+    }
+
+    /// Number of elements, same as the length of `iter`
+    #[inline]
+    pub fn len(&self) -> usize {
+        unsafe { element_count(&self.0) }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn refcount(&self) -> u32 {
+        self.0.gc.refcount
+    }
+
+    #[inline]
+    pub fn is_packed(&self) -> bool {
+        self.0.is_packed()
+    }
+
+    /// zend_array_is_list: keys are 0..n in order
+    pub fn is_list(&self) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        if self.is_packed() {
+            // keys are the positions, so holes break the sequence
+            return self.0.nNumUsed == self.0.nNumOfElements;
+        }
+        self.keys()
+            .zip(0..)
+            .all(|(key, idx)| key == ArrayKey::Int(idx))
+    }
+
+    /// Slots of a packed array by position, `None` for hash arrays
+    #[inline]
+    pub fn as_packed(&self) -> Option<PackedSlice<'_>> {
+        if self.is_packed() {
+            let slots = unsafe {
+                slice::from_raw_parts(self.0.arData as *const PackedSlot, self.0.nNumUsed as usize)
+            };
+            Some(PackedSlice { slots })
+        } else {
+            None
+        }
+    }
+
+    /// Compares like `$a <=> $b` for arrays: by element count, then by the values with the same keys,
+    /// which compare loosely like `==`. Arrays with different keys are not comparable and `Greater`
+    /// is returned. If `ordered`, keys must also be in the same order.
+    #[inline]
+    pub fn compare(&self, other: &ZArrayRef, ordered: bool) -> Ordering {
+        unsafe { zend::HashTable::compare(self.as_raw(), other.as_raw(), compare_zvals, ordered).cmp(&0) }
+    }
+
+    /// New reference to the array, it's copied on the first modification
+    #[inline]
+    pub fn to_zarray(&self) -> ZArray {
+        let ht = self.as_raw();
+        unsafe {
+            if (*ht).is_immutable() {
+                ZArray::from_raw(zend::HashTable::array_dup(ht))
+            } else {
+                (*ht).addref();
+                ZArray::from_raw(ht)
+            }
+        }
+    }
+
+    /// Calls `f` for every value, like zend_hash_apply. A panic in `f` stops the iteration.
+    pub fn apply<F: FnMut(&mut Zval) -> Apply>(&mut self, mut f: F) {
+        unsafe {
+            zend::HashTable::apply_with_argument(self.as_raw(), apply_trampoline::<F>, &mut f as *mut F as *mut c_void);
+        }
+    }
+}
+
+impl ArrayApi for ZArrayRef {
+    #[inline]
+    fn as_array_ref(&self) -> &ZArrayRef {
+        self
+    }
+
+    #[inline]
+    fn as_array_mut(&mut self) -> &mut ZArrayRef {
+        self
+    }
+}
+
+impl Index<ArrayIndex<'_>> for ZArrayRef {
+    type Output = Zval;
+
+    #[inline]
+    fn index(&self, index: ArrayIndex<'_>) -> &Self::Output {
+        self.get(index)
+            .expect("Value is not exists in array for given index")
+    }
+}
+
+/// Owned reference to a PHP array: it's destroyed with the last reference on drop, and
+/// copied before modification while shared (copy on write)
+///
+/// ```ignore
+/// let mut arr = ZArray::new();
+/// arr.insert(str_index!("answer"), 42);
+/// return arr.into_zval();
+/// ```
+#[repr(transparent)]
+pub struct ZArray(NonNull<zend::Array>);
+
+impl ZArray {
+    #[track_caller]
+    #[inline]
+    pub fn new() -> Self {
+        ArrayBuilder::new().build()
+    }
+
+    #[track_caller]
+    #[inline]
+    pub fn with_capacity(capacity: u32) -> Self {
+        ArrayBuilder::new().with_initial_min_size(capacity).build()
+    }
+
+    /// Packed array with room for `capacity` elements, for lists with keys 0..n
+    #[track_caller]
+    #[inline]
+    pub fn with_capacity_packed(capacity: u32) -> Self {
+        let arr = Self::with_capacity(capacity);
+        unsafe { zend::HashTable::real_init(arr.0.as_ptr(), true) };
+        arr
+    }
+
+    /// Packed list, the values are written into the slots directly like ZEND_HASH_FILL_PACKED
+    #[track_caller]
+    pub fn from_vec<T: IntoZval>(vec: Vec<T>) -> Self {
+        let len = u32::try_from(vec.len()).expect("Too many elements for array");
+        let arr = Self::with_capacity_packed(len);
+        let ht = arr.0.as_ptr();
+        unsafe {
+            for (idx, val) in vec.into_iter().enumerate() {
+                let val = val.into_zval().raw();
+                #[cfg(feature = "php82")]
+                {
+                    *((*ht).arData as *mut zend::Zval).add(idx) = val;
+                }
+                #[cfg(not(feature = "php82"))]
+                {
+                    let bucket = &mut *(*ht).arData.add(idx);
+                    bucket.val = val;
+                    bucket.h = idx as Ulong;
+                    bucket.key = ptr::null_mut();
+                }
+                // counted one by one, so a panic in into_zval leaves a consistent array
+                (*ht).nNumUsed = idx as u32 + 1;
+                (*ht).nNumOfElements = idx as u32 + 1;
+                (*ht).nNextFreeElement = idx as Long + 1;
+                (*ht).nInternalPointer = 0;
+            }
+        }
+        arr
+    }
+
+    /// Takes over one reference of `ht`
+    ///
+    /// # Safety
+    /// `ht` must point to a valid array
+    #[inline]
+    pub unsafe fn from_raw(ht: *mut zend::Array) -> Self {
+        ZArray(NonNull::new(ht).expect("null zend_array"))
+    }
+
+    /// Gives up the reference without releasing it
+    #[inline]
+    pub fn into_raw(self) -> *mut zend::Array {
+        let ht = self.0.as_ptr();
+        mem::forget(self);
+        ht
+    }
+
+    /// SEPARATE_ARRAY: replaces a shared array with a copy owned by this reference
+    fn separate(&mut self) {
+        let ht = self.0.as_ptr();
+        unsafe {
+            if (*ht).gc.refcount > 1 || (*ht).is_immutable() {
+                self.0 = NonNull::new_unchecked(zend::HashTable::array_dup(ht));
+                zend::HashTable::release(ht);
+            }
+        }
+    }
+}
+
+impl Default for ZArray {
+    #[track_caller]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for ZArray {
+    type Target = ZArrayRef;
+
+    #[inline]
+    fn deref(&self) -> &ZArrayRef {
+        unsafe { ZArrayRef::from_ptr(self.0.as_ptr()) }
+    }
+}
+
+impl DerefMut for ZArray {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut ZArrayRef {
+        self.separate();
+        unsafe { ZArrayRef::from_ptr_mut(self.0.as_ptr()) }
+    }
+}
+
+impl ArrayApi for ZArray {
+    #[inline]
+    fn as_array_ref(&self) -> &ZArrayRef {
+        self
+    }
+
+    #[inline]
+    fn as_array_mut(&mut self) -> &mut ZArrayRef {
+        self
+    }
+}
+
+/// zend_array_dup
+impl Clone for ZArray {
+    fn clone(&self) -> Self {
+        unsafe { ZArray::from_raw(zend::HashTable::array_dup(self.0.as_ptr())) }
+    }
+}
+
+impl Drop for ZArray {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { zend::HashTable::release(self.0.as_ptr()) }
+    }
+}
+
+/// Copyable handle to a PHP array, without ownership
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Array(*mut zend::Array);
+
+impl Array {
+    #[inline]
+    pub fn raw(&self) -> *mut zend::Array {
+        self.0
+    }
+
+    #[inline]
+    pub fn as_array_ref(&self) -> &ZArrayRef {
+        unsafe { ZArrayRef::from_ptr(self.0) }
+    }
+
+    /// Mutable view of the array, without separation
+    ///
+    /// # Safety
+    ///
+    /// The array must not be shared: handles are `Copy` and the refcount may be above one,
+    /// use `ArrayApi::as_array_mut` on the owning `Zval` or a `ZArray` to separate it first.
+    #[inline]
+    pub unsafe fn as_array_mut(&mut self) -> &mut ZArrayRef {
+        ZArrayRef::from_ptr_mut(self.0)
+    }
+
+    /// Calls `f` for every value, like zend_hash_apply. A panic in `f` stops the iteration.
+    ///
+    /// # Safety
+    ///
+    /// Same as `as_array_mut`, the array is modified in place.
+    pub unsafe fn apply<F: FnMut(&mut Zval) -> Apply>(&mut self, f: F) {
+        self.as_array_mut().apply(f)
+    }
+}
+
+/// What `ZArrayRef::apply` does after the callback returns
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Apply {
+    Keep,
+    Remove,
+    Stop,
+}
+
+extern "C" fn apply_trampoline<F: FnMut(&mut Zval) -> Apply>(zv: *mut zend::Zval, f: *mut c_void) -> c_int {
+    let f = unsafe { &mut *(f as *mut F) };
+    let result = match catch_panic(PanicContext::Request, || f(unsafe { (*zv).as_safe_mut() })) {
+        Some(Apply::Keep) => zend::ZEND_HASH_APPLY_KEEP,
+        Some(Apply::Remove) => zend::ZEND_HASH_APPLY_REMOVE,
+        Some(Apply::Stop) | None => zend::ZEND_HASH_APPLY_STOP,
+    };
+    result as c_int
 }
 
 // todo: maybe unsafe!
@@ -235,18 +977,12 @@ impl Deref for Array {
     }
 }
 
-impl DerefMut for Array {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe { &mut *self.0 }
-    }
-}
-
 impl Index<ArrayIndex<'_>> for Array {
     type Output = Zval;
 
     #[inline]
     fn index(&self, index: ArrayIndex<'_>) -> &Self::Output {
-        self.get(index)
+        self.as_array_ref().get(index)
             .expect("Value is not exists in array for given index")
     }
 }
@@ -260,12 +996,15 @@ impl ToSafe for *mut zend::Array {
     }
 }
 
+/// Buckets in order, holes are skipped, see `ArrayApi::buckets_iter`
 pub struct BucketsIter<'a> {
     buckets: &'a [zend::Bucket],
     idx: usize,
 }
 
 impl BucketsIter<'_> {
+    /// # Safety
+    /// `raw` must point to a valid array that outlives the iterator, not a packed one since PHP 8.2
     #[inline]
     pub unsafe fn from_raw(raw: *const zend::Array) -> Self {
         BucketsIter {
@@ -296,11 +1035,15 @@ impl<'a> Iterator for BucketsIter<'a> {
     }
 }
 
+/// Mutable `BucketsIter`, see `ArrayApi::buckets_iter_mut`
 pub struct BucketsIterMut<'a> {
     rest_buckets: &'a mut [zend::Bucket],
 }
 
 impl BucketsIterMut<'_> {
+    /// # Safety
+    /// `raw` must point to a valid array that outlives the iterator, without other references,
+    /// not a packed one since PHP 8.2
     #[inline]
     pub unsafe fn from_raw(raw: *mut zend::Array) -> Self {
         BucketsIterMut {
@@ -335,4 +1078,235 @@ impl<'a> Iterator for BucketsIterMut<'a> {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// nNumOfElements, which also counts the UNDEF indirect elements of symbol tables flagged with
+/// HASH_FLAG_HAS_EMPTY_IND: those are counted by walking the slots
+unsafe fn element_count(ht: *const zend::Array) -> usize {
+    if (*ht).has_empty_ind() {
+        (0..(*ht).nNumUsed as usize).filter(|&idx| slot(ht, idx).is_some()).count()
+    } else {
+        (*ht).nNumOfElements as usize
+    }
+}
+
+/// Iterator over `(ArrayKey, &Zval)`, see `ArrayApi::iter`
+pub struct Iter<'a> {
+    ht: *const zend::Array,
+    front: usize,
+    back: usize,
+    remaining: usize,
+    _marker: PhantomData<&'a zend::Array>,
+}
+
+impl Iter<'_> {
+    /// # Safety
+    /// `raw` must point to a valid array that outlives the iterator
+    #[inline]
+    pub unsafe fn from_raw(raw: *const zend::Array) -> Self {
+        Iter {
+            ht: raw,
+            front: 0,
+            back: (*raw).nNumUsed as usize,
+            remaining: element_count(raw),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (ArrayKey, &'a Zval);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.front < self.back {
+            let idx = self.front;
+            self.front += 1;
+            if let Some((key, zv)) = unsafe { slot(self.ht, idx) } {
+                self.remaining = self.remaining.saturating_sub(1);
+                return Some((key, unsafe { (*zv).as_safe() }));
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while self.front < self.back {
+            self.back -= 1;
+            if let Some((key, zv)) = unsafe { slot(self.ht, self.back) } {
+                self.remaining = self.remaining.saturating_sub(1);
+                return Some((key, unsafe { (*zv).as_safe() }));
+            }
+        }
+        None
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+/// Iterator over `(ArrayKey, &mut Zval)`, see `ArrayApi::iter_mut`
+pub struct IterMut<'a> {
+    ht: *mut zend::Array,
+    front: usize,
+    back: usize,
+    remaining: usize,
+    _marker: PhantomData<&'a mut zend::Array>,
+}
+
+impl IterMut<'_> {
+    /// # Safety
+    /// `raw` must point to a valid array that outlives the iterator, without other references
+    #[inline]
+    pub unsafe fn from_raw(raw: *mut zend::Array) -> Self {
+        IterMut {
+            ht: raw,
+            front: 0,
+            back: (*raw).nNumUsed as usize,
+            remaining: element_count(raw),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a> Iterator for IterMut<'a> {
+    type Item = (ArrayKey, &'a mut Zval);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.front < self.back {
+            let idx = self.front;
+            self.front += 1;
+            // every slot is yielded at most once
+            if let Some((key, zv)) = unsafe { slot(self.ht, idx) } {
+                self.remaining = self.remaining.saturating_sub(1);
+                return Some((key, unsafe { (*zv).as_safe_mut() }));
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl DoubleEndedIterator for IterMut<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while self.front < self.back {
+            self.back -= 1;
+            if let Some((key, zv)) = unsafe { slot(self.ht, self.back) } {
+                self.remaining = self.remaining.saturating_sub(1);
+                return Some((key, unsafe { (*zv).as_safe_mut() }));
+            }
+        }
+        None
+    }
+}
+
+impl ExactSizeIterator for IterMut<'_> {}
+
+pub struct Keys<'a>(Iter<'a>);
+
+impl Iterator for Keys<'_> {
+    type Item = ArrayKey;
+
+    #[inline]
+    fn next(&mut self) -> Option<ArrayKey> {
+        self.0.next().map(|(key, _)| key)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Keys<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<ArrayKey> {
+        self.0.next_back().map(|(key, _)| key)
+    }
+}
+
+impl ExactSizeIterator for Keys<'_> {}
+
+pub struct Values<'a>(Iter<'a>);
+
+impl<'a> Iterator for Values<'a> {
+    type Item = &'a Zval;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a Zval> {
+        self.0.next().map(|(_, zv)| zv)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for Values<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a Zval> {
+        self.0.next_back().map(|(_, zv)| zv)
+    }
+}
+
+impl ExactSizeIterator for Values<'_> {}
+
+macro_rules! impl_into_iterator {
+    ($($t:ty),*) => {$(
+        impl<'a> IntoIterator for &'a $t {
+            type Item = (ArrayKey, &'a Zval);
+            type IntoIter = Iter<'a>;
+
+            #[inline]
+            fn into_iter(self) -> Iter<'a> {
+                self.iter()
+            }
+        }
+    )*}
+}
+
+impl_into_iterator!(ZArrayRef, ZArray);
+
+impl<'a> IntoIterator for &'a Array {
+    type Item = (ArrayKey, &'a Zval);
+    type IntoIter = Iter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a> {
+        self.as_array_ref().iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut ZArrayRef {
+    type Item = (ArrayKey, &'a mut Zval);
+    type IntoIter = IterMut<'a>;
+
+    #[inline]
+    fn into_iter(self) -> IterMut<'a> {
+        self.iter_mut()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut ZArray {
+    type Item = (ArrayKey, &'a mut Zval);
+    type IntoIter = IterMut<'a>;
+
+    #[inline]
+    fn into_iter(self) -> IterMut<'a> {
+        self.iter_mut()
+    }
+}
@@ -0,0 +1,385 @@
+use std::str;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::hash::BuildHasher;
+use crate::{zend, Zval, Value, ZendStr, Long, Double, Array, ArrayApi, ArrayBuilder, ArrayIndex,
+    ArrayKey, Iter, ZArray, ZArrayRef, ToSafe};
+
+/// Conversion from a borrowed PHP value into a Rust value
+pub trait FromZval<'a>: Sized {
+    /// PHP type name used in parameter error messages
+    const TYPE_NAME: &'static str;
+
+    fn from_zval(zv: &'a Zval) -> Option<Self>;
+
+    /// Value for an argument that was not passed, `None` if the argument is required
+    #[inline]
+    fn missing() -> Option<Self> {
+        None
+    }
+}
+
+impl<'a> FromZval<'a> for &'a Zval {
+    const TYPE_NAME: &'static str = "mixed";
+
+    #[inline]
+    fn from_zval(zv: &'a Zval) -> Option<Self> {
+        Some(zv)
+    }
+}
+
+impl FromZval<'_> for Long {
+    const TYPE_NAME: &'static str = "integer";
+
+    #[inline]
+    fn from_zval(zv: &Zval) -> Option<Self> {
+        match zv.value() {
+            Value::Long(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl FromZval<'_> for Double {
+    const TYPE_NAME: &'static str = "float";
+
+    #[inline]
+    fn from_zval(zv: &Zval) -> Option<Self> {
+        match zv.value() {
+            Value::Double(v) => Some(v),
+            Value::Long(v) => Some(v as Double),
+            _ => None,
+        }
+    }
+}
+
+impl FromZval<'_> for bool {
+    const TYPE_NAME: &'static str = "boolean";
+
+    #[inline]
+    fn from_zval(zv: &Zval) -> Option<Self> {
+        match zv.value() {
+            Value::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+/// Strings that are not valid UTF-8 are rejected
+impl<'a> FromZval<'a> for &'a str {
+    const TYPE_NAME: &'static str = "string";
+
+    #[inline]
+    fn from_zval(zv: &'a Zval) -> Option<Self> {
+        match zv.value() {
+            Value::String(_) => str::from_utf8(<&[u8]>::from_zval(zv)?).ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromZval<'_> for String {
+    const TYPE_NAME: &'static str = "string";
+
+    #[inline]
+    fn from_zval(zv: &Zval) -> Option<Self> {
+        <&str>::from_zval(zv).map(String::from)
+    }
+}
+
+/// New reference to the string
+impl FromZval<'_> for ZendStr {
+    const TYPE_NAME: &'static str = "string";
+
+    #[inline]
+    fn from_zval(zv: &Zval) -> Option<Self> {
+        match zv.value() {
+            Value::String(s) => Some(s.to_zend_str()),
+            _ => None,
+        }
+    }
+}
+
+/// String bytes, binary safe
+impl<'a> FromZval<'a> for &'a [u8] {
+    const TYPE_NAME: &'static str = "string";
+
+    #[inline]
+    fn from_zval(zv: &'a Zval) -> Option<Self> {
+        match zv.value() {
+            Value::String(s) => Some(unsafe { &*(s.as_bytes() as *const [u8]) }),
+            _ => None,
+        }
+    }
+}
+
+/// String bytes, binary safe
+impl FromZval<'_> for Vec<u8> {
+    const TYPE_NAME: &'static str = "string";
+
+    #[inline]
+    fn from_zval(zv: &Zval) -> Option<Self> {
+        <&[u8]>::from_zval(zv).map(<[u8]>::to_vec)
+    }
+}
+
+impl<'a> FromZval<'a> for &'a ZArrayRef {
+    const TYPE_NAME: &'static str = "array";
+
+    #[inline]
+    fn from_zval(zv: &'a Zval) -> Option<Self> {
+        zv.as_array().map(|arr| unsafe { ZArrayRef::from_ptr(arr.raw()) })
+    }
+}
+
+/// New reference to the array
+impl FromZval<'_> for ZArray {
+    const TYPE_NAME: &'static str = "array";
+
+    #[inline]
+    fn from_zval(zv: &Zval) -> Option<Self> {
+        <&ZArrayRef>::from_zval(zv).map(ZArrayRef::to_zarray)
+    }
+}
+
+/// Array values in iteration order, keys are ignored
+impl<'a, T: FromZval<'a>> FromZval<'a> for Vec<T> {
+    const TYPE_NAME: &'static str = "array";
+
+    fn from_zval(zv: &'a Zval) -> Option<Self> {
+        let arr = <&ZArrayRef>::from_zval(zv)?;
+        let mut vec = Vec::with_capacity(arr.len());
+        match arr.as_packed() {
+            Some(packed) => for val in packed.iter() {
+                vec.push(T::from_zval(val)?);
+            },
+            None => for (_, val) in arr.iter() {
+                vec.push(T::from_zval(val)?);
+            },
+        }
+        Some(vec)
+    }
+}
+
+/// Integer keys are converted to their decimal representation
+impl<'a, T: FromZval<'a>, S: BuildHasher + Default> FromZval<'a> for HashMap<String, T, S> {
+    const TYPE_NAME: &'static str = "array";
+
+    fn from_zval(zv: &'a Zval) -> Option<Self> {
+        let arr = zv.as_array()?;
+        let mut map = HashMap::with_capacity_and_hasher(arr.nNumOfElements as usize, S::default());
+        for (key, val) in unsafe { Iter::from_raw(arr.raw()) } {
+            let key = match key {
+                ArrayKey::Str(key) => key.to_str().ok()?.to_owned(),
+                ArrayKey::Int(h) => (h as Long).to_string(),
+            };
+            map.insert(key, T::from_zval(val)?);
+        }
+        Some(map)
+    }
+}
+
+/// `null` is converted to `Some(None)`
+impl<'a, T: FromZval<'a>> FromZval<'a> for Option<T> {
+    const TYPE_NAME: &'static str = T::TYPE_NAME;
+
+    #[inline]
+    fn from_zval(zv: &'a Zval) -> Option<Self> {
+        match zv.value() {
+            Value::Null => Some(None),
+            _ => T::from_zval(zv).map(Some),
+        }
+    }
+
+    #[inline]
+    fn missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+/// Conversion of a Rust value into a PHP value
+pub trait IntoZval: Sized {
+    fn into_zval(self) -> Zval;
+
+    /// Lossless conversion: `None` where `into_zval` would lose precision, e.g. for integers
+    /// above `zend_long` that `into_zval` turns into a float
+    #[inline]
+    fn try_into_zval(self) -> Option<Zval> {
+        Some(self.into_zval())
+    }
+}
+
+impl IntoZval for Zval {
+    #[inline]
+    fn into_zval(self) -> Zval {
+        self
+    }
+}
+
+impl IntoZval for () {
+    #[inline]
+    fn into_zval(self) -> Zval {
+        let mut zv = zend::Zval::default();
+        zv.set_null();
+        zv.into_safe()
+    }
+}
+
+impl IntoZval for bool {
+    #[inline]
+    fn into_zval(self) -> Zval {
+        let mut zv = zend::Zval::default();
+        zv.set_bool(self);
+        zv.into_safe()
+    }
+}
+
+/// Integers that don't fit into `zend_long` are converted to float, like PHP does on overflow,
+/// losing precision. `try_into_zval` returns `None` for them instead.
+macro_rules! impl_into_zval_for_int {
+    ($($t:ty),*) => {$(
+        impl IntoZval for $t {
+            #[inline]
+            fn into_zval(self) -> Zval {
+                match Long::try_from(self) {
+                    Ok(v) => Zval::from(v),
+                    Err(_) => Zval::from(self as Double),
+                }
+            }
+
+            #[inline]
+            fn try_into_zval(self) -> Option<Zval> {
+                Long::try_from(self).ok().map(Zval::from)
+            }
+        }
+    )*}
+}
+
+impl_into_zval_for_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl IntoZval for Double {
+    #[inline]
+    fn into_zval(self) -> Zval {
+        Zval::from(self)
+    }
+}
+
+impl IntoZval for f32 {
+    #[inline]
+    fn into_zval(self) -> Zval {
+        Zval::from(self as Double)
+    }
+}
+
+impl IntoZval for &'_ str {
+    #[inline]
+    fn into_zval(self) -> Zval {
+        Zval::from(self)
+    }
+}
+
+impl IntoZval for String {
+    #[inline]
+    fn into_zval(self) -> Zval {
+        Zval::from(self.as_str())
+    }
+}
+
+/// `None` is converted to `null`
+impl<T: IntoZval> IntoZval for Option<T> {
+    #[inline]
+    fn into_zval(self) -> Zval {
+        match self {
+            Some(v) => v.into_zval(),
+            None => ().into_zval(),
+        }
+    }
+
+    #[inline]
+    fn try_into_zval(self) -> Option<Zval> {
+        match self {
+            Some(v) => v.try_into_zval(),
+            None => Some(().into_zval()),
+        }
+    }
+}
+
+/// New reference to the array, the handle doesn't own one
+impl IntoZval for Array {
+    #[inline]
+    fn into_zval(self) -> Zval {
+        self.as_array_ref().to_zarray().into_zval()
+    }
+}
+
+/// The zval takes over the reference
+impl IntoZval for ZArray {
+    #[inline]
+    fn into_zval(self) -> Zval {
+        let mut zv = zend::Zval::default();
+        zv.set_arr(unsafe { &mut *self.into_raw() });
+        zv.into_safe()
+    }
+}
+
+/// Packed array
+impl<T: IntoZval> IntoZval for Vec<T> {
+    #[inline]
+    fn into_zval(self) -> Zval {
+        ZArray::from_vec(self).into_zval()
+    }
+}
+
+fn map_into_zval<K, V, I>(len: usize, iter: I) -> Zval
+where
+    K: AsRef<str>,
+    V: IntoZval,
+    I: Iterator<Item = (K, V)>,
+{
+    let mut arr = ArrayBuilder::new()
+        .with_initial_min_size(len as u32)
+        .build();
+    for (key, value) in iter {
+        let key = key.as_ref();
+        arr.insert(ArrayIndex::Cstr(key.as_ptr() as *const _, key.len()), value);
+    }
+    arr.into_zval()
+}
+
+impl<K: AsRef<str>, V: IntoZval, S: BuildHasher> IntoZval for HashMap<K, V, S> {
+    #[inline]
+    fn into_zval(self) -> Zval {
+        map_into_zval(self.len(), self.into_iter())
+    }
+}
+
+impl<K: AsRef<str>, V: IntoZval> IntoZval for BTreeMap<K, V> {
+    #[inline]
+    fn into_zval(self) -> Zval {
+        map_into_zval(self.len(), self.into_iter())
+    }
+}
+
+/// Tuples are converted to packed arrays
+macro_rules! impl_into_zval_for_tuple {
+    ($($n:tt: $t:ident),+) => {
+        impl<$($t: IntoZval),+> IntoZval for ($($t,)+) {
+            fn into_zval(self) -> Zval {
+                let arr = ArrayBuilder::new()
+                    .with_initial_min_size([$($n),+].len() as u32)
+                    .build();
+                $(unsafe { zend::HashTable::next_index_insert(arr.as_raw(), self.$n.into_zval().as_raw_mut()) };)+
+                arr.into_zval()
+            }
+        }
+    }
+}
+
+impl_into_zval_for_tuple!(0: A);
+impl_into_zval_for_tuple!(0: A, 1: B);
+impl_into_zval_for_tuple!(0: A, 1: B, 2: C);
+impl_into_zval_for_tuple!(0: A, 1: B, 2: C, 3: D);
+impl_into_zval_for_tuple!(0: A, 1: B, 2: C, 3: D, 4: E);
+impl_into_zval_for_tuple!(0: A, 1: B, 2: C, 3: D, 4: E, 5: F);
+impl_into_zval_for_tuple!(0: A, 1: B, 2: C, 3: D, 4: E, 5: F, 6: G);
+impl_into_zval_for_tuple!(0: A, 1: B, 2: C, 3: D, 4: E, 5: F, 6: G, 7: H);
@@ -1,6 +1,7 @@
-use std::{ptr, slice};
+use std::{ptr, slice, fmt, error};
+use std::ffi::CStr;
 use std::os::raw::c_int;
-use crate::{c_str, zend, Zval};
+use crate::{c_str, zend, Zval, FromZval, ToSafe};
 
 #[repr(C)]
 #[derive(Copy, Clone)]
@@ -27,4 +28,107 @@ impl ExecuteData {
             }
         }
     }
-}
\ No newline at end of file
+
+    /// Object the method is called on, `None` for functions and static methods
+    #[inline]
+    pub fn this(&self) -> Option<&Zval> {
+        // PHP 7.0 tags `This` as an object on every call, static ones have a null object
+        if self.0.This.is_object() && !unsafe { self.0.This.value.obj }.is_null() {
+            Some(self.0.This.as_safe())
+        } else {
+            None
+        }
+    }
+
+    /// Raw argument value, `n` starts from 0
+    #[inline]
+    pub fn arg_zval(&self, n: u32) -> Option<&Zval> {
+        if n < self.num_args() {
+            Some(unsafe { &*(self.0.arg(n + 1) as *const Zval) })
+        } else {
+            None
+        }
+    }
+
+    /// Converted argument value, `n` starts from 0
+    #[inline]
+    pub fn arg<'a, T: FromZval<'a>>(&'a self, n: u32) -> Result<T, ArgError> {
+        let num = n as usize + 1;
+        match self.arg_zval(n) {
+            Some(zv) => T::from_zval(zv).ok_or_else(|| ArgError::wrong_type::<T>(num, zv)),
+            None => T::missing().ok_or(ArgError::Missing { num }),
+        }
+    }
+
+    /// Converted arguments as a tuple: `data.args::<(Long, Option<&str>)>()`
+    #[inline]
+    pub fn args<'a, T: FromArgs<'a>>(&'a self) -> Result<T, ArgError> {
+        T::from_args(self)
+    }
+}
+
+/// Argument conversion error
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgError {
+    /// Required argument is not passed
+    Missing { num: usize },
+    /// Argument value can't be converted to the expected type
+    WrongType { num: usize, expected: &'static str, given: String },
+}
+
+impl ArgError {
+    pub fn wrong_type<'a, T: FromZval<'a>>(num: usize, zv: &Zval) -> Self {
+        let given = unsafe { CStr::from_ptr(zend::zend_zval_type_name(zv.as_raw())) };
+        ArgError::WrongType {
+            num,
+            expected: T::TYPE_NAME,
+            given: given.to_string_lossy().into_owned(),
+        }
+    }
+
+    /// Argument number, starts from 1
+    pub fn num(&self) -> usize {
+        match *self {
+            ArgError::Missing { num } => num,
+            ArgError::WrongType { num, .. } => num,
+        }
+    }
+}
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArgError::Missing { num } =>
+                write!(f, "expects parameter {} to be passed", num),
+            ArgError::WrongType { num, expected, given } =>
+                write!(f, "expects parameter {} to be {}, {} given", num, expected, given),
+        }
+    }
+}
+
+impl error::Error for ArgError {}
+
+/// Tuple of arguments extracted with `ExecuteData::args`
+pub trait FromArgs<'a>: Sized {
+    fn from_args(data: &'a ExecuteData) -> Result<Self, ArgError>;
+}
+
+macro_rules! impl_from_args {
+    ($($n:tt: $t:ident),+) => {
+        impl<'a, $($t: FromZval<'a>),+> FromArgs<'a> for ($($t,)+) {
+            #[inline]
+            fn from_args(data: &'a ExecuteData) -> Result<Self, ArgError> {
+                Ok(($(data.arg::<$t>($n)?,)+))
+            }
+        }
+    }
+}
+
+impl_from_args!(0: A);
+impl_from_args!(0: A, 1: B);
+impl_from_args!(0: A, 1: B, 2: C);
+impl_from_args!(0: A, 1: B, 2: C, 3: D);
+impl_from_args!(0: A, 1: B, 2: C, 3: D, 4: E);
+impl_from_args!(0: A, 1: B, 2: C, 3: D, 4: E, 5: F);
+impl_from_args!(0: A, 1: B, 2: C, 3: D, 4: E, 5: F, 6: G);
+impl_from_args!(0: A, 1: B, 2: C, 3: D, 4: E, 5: F, 6: G, 7: H);
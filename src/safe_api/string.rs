@@ -1,24 +1,78 @@
-use std::{slice, cmp::PartialEq};
-use crate::{zend, ToSafe};
+use std::{fmt, mem, ptr, slice, str, borrow::Cow, cmp::PartialEq, ops::Deref, ptr::NonNull};
+use std::sync::atomic::{AtomicPtr, Ordering};
+use crate::{zend, ToSafe, Zval, Ulong, IntoZval};
 
+/// Borrowed PHP string, doesn't own a reference
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct ZString(*mut zend::String);
 
 impl ZString {
+    /// Raw bytes, PHP strings are binary safe
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts((*self.0).val.as_ptr(), (*self.0).len) }
+    }
+
+    #[inline]
+    pub fn to_str(&self) -> Result<&str, str::Utf8Error> {
+        str::from_utf8(self.as_bytes())
+    }
+
+    /// Invalid UTF-8 sequences are replaced with U+FFFD
+    #[inline]
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self.as_bytes())
+    }
+
+    /// Panics if the string is not valid UTF-8, PHP strings are often binary
+    #[deprecated(note = "use `to_str` or `to_string_lossy`, PHP strings may not be valid UTF-8")]
     #[inline]
     pub fn as_str(&self) -> &str {
-        unsafe {
-            std::str::from_utf8_unchecked(
-                slice::from_raw_parts((*self.0).val.as_ptr(), (*self.0).len)
-            )
-        }
+        self.to_str().expect("PHP string is not valid UTF-8")
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        unsafe { (*self.0).len }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn is_interned(&self) -> bool {
+        unsafe { (*self.0).is_interned() }
+    }
+
+    #[inline]
+    pub fn is_persistent(&self) -> bool {
+        unsafe { (*self.0).is_persistent() }
+    }
+
+    #[inline]
+    pub fn refcount(&self) -> u32 {
+        unsafe { (*self.0).gc.refcount }
+    }
+
+    /// DJBX33A hash used by hash tables, computed once and cached in the string
+    #[inline]
+    pub fn hash(&self) -> Ulong {
+        unsafe { (*self.0).hash_val() }
+    }
+
+    /// New reference to the same string
+    #[inline]
+    pub fn to_zend_str(&self) -> ZendStr {
+        unsafe { ZendStr::from_raw(zend::String::copy(self.0)) }
     }
 }
 
 impl PartialEq for ZString {
     fn eq(&self, other: &ZString) -> bool {
-        self.0 == other.0 || self.as_str() == other.as_str()
+        self.0 == other.0 || self.as_bytes() == other.as_bytes()
     }
 }
 
@@ -47,4 +101,305 @@ impl ToSafe for *mut zend::String {
     fn into_safe(self) -> Self::SafeType {
         ZString(self)
     }
-}
\ No newline at end of file
+}
+
+/// Owned reference to a PHP string: cloning adds a reference and dropping releases it,
+/// freeing the string with the last one. Interned strings are not refcounted.
+///
+/// ```ignore
+/// let name = ZendStr::new("counter");
+/// assert_eq!(name.to_str(), Ok("counter"));
+/// return name.into_zval();
+/// ```
+#[repr(transparent)]
+pub struct ZendStr(NonNull<zend::String>);
+
+impl ZendStr {
+    /// Request-bound string, freed at the end of the request at the latest
+    #[track_caller]
+    #[inline]
+    pub fn new(s: &str) -> Self {
+        unsafe { Self::from_raw(zend::String::init(s, false)) }
+    }
+
+    /// String allocated with malloc, it outlives requests
+    #[track_caller]
+    #[inline]
+    pub fn new_persistent(s: &str) -> Self {
+        unsafe { Self::from_raw(zend::String::init(s, true)) }
+    }
+
+    /// Request-bound string, `bytes` may contain NUL bytes
+    #[track_caller]
+    #[inline]
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        unsafe { Self::from_raw(zend::String::init_bytes(bytes, false)) }
+    }
+
+    #[track_caller]
+    #[inline]
+    pub fn from_bytes_persistent(bytes: &[u8]) -> Self {
+        unsafe { Self::from_raw(zend::String::init_bytes(bytes, true)) }
+    }
+
+    /// Takes over one reference of `ptr`
+    ///
+    /// # Safety
+    /// `ptr` must point to a valid string
+    #[inline]
+    pub unsafe fn from_raw(ptr: *mut zend::String) -> Self {
+        ZendStr(NonNull::new(ptr).expect("null zend_string"))
+    }
+
+    /// Gives up the reference without releasing it
+    #[inline]
+    pub fn into_raw(self) -> *mut zend::String {
+        let ptr = self.0.as_ptr();
+        mem::forget(self);
+        ptr
+    }
+
+    #[inline]
+    pub fn as_ptr(&self) -> *mut zend::String {
+        self.0.as_ptr()
+    }
+
+    #[inline]
+    pub fn as_zstring(&self) -> ZString {
+        ZString(self.0.as_ptr())
+    }
+
+    /// zend_new_interned_string: returns the interned string with the same content, this
+    /// reference is released if one already exists. The string is returned as is when the
+    /// engine doesn't intern strings at this point.
+    pub fn intern(self) -> ZendStr {
+        match unsafe { zend::zend_new_interned_string } {
+            Some(new_interned_string) => unsafe { ZendStr::from_raw(new_interned_string(self.into_raw())) },
+            None => self,
+        }
+    }
+}
+
+impl Deref for ZendStr {
+    type Target = ZString;
+
+    #[inline]
+    fn deref(&self) -> &ZString {
+        unsafe { &*(self as *const Self as *const ZString) }
+    }
+}
+
+impl Clone for ZendStr {
+    #[inline]
+    fn clone(&self) -> Self {
+        self.to_zend_str()
+    }
+}
+
+impl Drop for ZendStr {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { zend::String::release(self.0.as_ptr()) }
+    }
+}
+
+impl PartialEq for ZendStr {
+    fn eq(&self, other: &ZendStr) -> bool {
+        self.as_zstring() == other.as_zstring()
+    }
+}
+
+impl fmt::Debug for ZendStr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_string_lossy(), f)
+    }
+}
+
+impl fmt::Display for ZendStr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.to_string_lossy(), f)
+    }
+}
+
+impl From<&str> for ZendStr {
+    #[track_caller]
+    #[inline]
+    fn from(from: &str) -> Self {
+        ZendStr::new(from)
+    }
+}
+
+impl From<&[u8]> for ZendStr {
+    #[track_caller]
+    #[inline]
+    fn from(from: &[u8]) -> Self {
+        ZendStr::from_bytes(from)
+    }
+}
+
+impl From<ZString> for ZendStr {
+    #[inline]
+    fn from(from: ZString) -> Self {
+        from.to_zend_str()
+    }
+}
+
+/// The zval takes over the reference
+impl IntoZval for ZendStr {
+    #[inline]
+    fn into_zval(self) -> Zval {
+        let mut zv = zend::Zval::default();
+        unsafe { zv.set_str(&mut *self.into_raw()) };
+        zv.into_safe()
+    }
+}
+
+/// Persistent string created on first use and never freed, shared by all threads and requests.
+/// Use it through `zstr!`.
+pub struct InternedStr {
+    value: &'static str,
+    ptr: AtomicPtr<zend::String>,
+}
+
+impl InternedStr {
+    pub const fn new(value: &'static str) -> Self {
+        InternedStr { value, ptr: AtomicPtr::new(ptr::null_mut()) }
+    }
+
+    pub fn get(&self) -> ZString {
+        let s = self.ptr.load(Ordering::Acquire);
+        if !s.is_null() {
+            return ZString(s);
+        }
+        let new = zend::String::init(self.value, true);
+        unsafe { zend::String::make_permanent(new) };
+        match self.ptr.compare_exchange(ptr::null_mut(), new, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => ZString(new),
+            Err(existing) => {
+                zend::pefree(new, true);
+                ZString(existing)
+            },
+        }
+    }
+}
+
+/// Builds a string in a growing emalloc'ed buffer, like smart_str: `finish` hands the buffer
+/// over as the string without copying it.
+///
+/// ```ignore
+/// let mut s = ZStringBuilder::new();
+/// write!(s, "{} items", count)?;
+/// s.push_bytes(b"\0raw");
+/// return s.finish().into_zval();
+/// ```
+pub struct ZStringBuilder {
+    /// Null until the first push
+    s: *mut zend::String,
+    len: usize,
+    capacity: usize,
+}
+
+impl ZStringBuilder {
+    /// SMART_STR_PREALLOC
+    const PREALLOC: usize = 128;
+
+    #[inline]
+    pub fn new() -> Self {
+        ZStringBuilder { s: ptr::null_mut(), len: 0, capacity: 0 }
+    }
+
+    #[track_caller]
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut builder = Self::new();
+        builder.reserve(capacity);
+        builder
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        if self.s.is_null() {
+            &[]
+        } else {
+            unsafe { slice::from_raw_parts((*self.s).val.as_ptr(), self.len) }
+        }
+    }
+
+    /// Makes room for `additional` more bytes, growing the buffer by at least half
+    #[track_caller]
+    pub fn reserve(&mut self, additional: usize) {
+        let required = self.len.checked_add(additional).expect("string length overflow");
+        if required <= self.capacity && !self.s.is_null() {
+            return;
+        }
+        let capacity = required.max(self.capacity + self.capacity / 2).max(Self::PREALLOC);
+        // header, bytes and the terminating NUL
+        let size = zend::ZEND_MM_ALIGNED_SIZE(mem::size_of::<zend::String>() + capacity);
+        self.s = if self.s.is_null() {
+            zend::String::alloc(capacity, false)
+        } else {
+            zend::erealloc(self.s, size)
+        };
+        self.capacity = capacity;
+    }
+
+    #[track_caller]
+    pub fn push_bytes(&mut self, bytes: &[u8]) {
+        self.reserve(bytes.len());
+        unsafe {
+            ptr::copy_nonoverlapping(bytes.as_ptr(), (*self.s).val.as_mut_ptr().add(self.len), bytes.len());
+        }
+        self.len += bytes.len();
+    }
+
+    #[track_caller]
+    #[inline]
+    pub fn push_str(&mut self, s: &str) {
+        self.push_bytes(s.as_bytes());
+    }
+
+    /// Terminates the buffer and turns it into the string
+    #[track_caller]
+    pub fn finish(mut self) -> ZendStr {
+        if self.s.is_null() {
+            return ZendStr::from_bytes(b"");
+        }
+        let s = mem::replace(&mut self.s, ptr::null_mut());
+        unsafe {
+            (*s).len = self.len;
+            *(*s).val.as_mut_ptr().add(self.len) = b'\0';
+            ZendStr::from_raw(s)
+        }
+    }
+}
+
+impl Default for ZStringBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Write for ZStringBuilder {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl Drop for ZStringBuilder {
+    fn drop(&mut self) {
+        if !self.s.is_null() {
+            zend::efree(self.s);
+        }
+    }
+}
@@ -1,10 +1,11 @@
 use std::{
+    mem,
     ops::{Deref, DerefMut, Index},
-    cmp::PartialEq,
+    cmp::{Ordering, PartialEq},
 };
 use crate::{
-    zend, Long, Double, ZString, Array, ArrayApi, ArrayIndex, BucketsIter, BucketsIterMut,
-    VoidPtrMut, Refcounted, ToSafe
+    zend, Long, Double, ZString, ZendStr, Array, ArrayApi, ArrayIndex, ZArrayRef,
+    VoidPtrMut, Refcounted, ToSafe, IntoZval, Object, PhpClass
 };
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -18,6 +19,7 @@ pub enum Value {
     Reference(*mut zend::Reference),
     String(ZString),
     Array(Array),
+    Object(Object),
     Zval(*mut Zval),
     Ptr(VoidPtrMut),
     Function(*mut zend::Function),
@@ -41,7 +43,7 @@ impl Zval {
                 zend::IS_DOUBLE => Value::Double(self.0.value.dval),
                 zend::IS_STRING => Value::String(self.0.value.str.into_safe()),
                 zend::IS_ARRAY => Value::Array(self.0.value.arr.into_safe()),
-//            zend::IS_OBJECT => Value::Object(self.0.value.obj),
+                zend::IS_OBJECT => Value::Object(self.0.value.obj.into_safe()),
 //            zend::IS_RESOURCE,
                 zend::IS_REFERENCE => Value::Reference(self.0.value.ref_),
                 _ => Value::Unknown,
@@ -61,69 +63,91 @@ impl Zval {
     }
 
     #[inline]
-    pub fn raw(self) -> zend::Zval {
-        self.0
+    pub fn as_object(&self) -> Option<Object> {
+        unsafe {
+            if self.0.is_object() {
+                Some(self.0.value.obj.into_safe())
+            } else {
+                None
+            }
+        }
     }
 
+    /// Rust value of an object created by a `PhpClass`
     #[inline]
-    pub fn as_raw(&self) -> *const zend::Zval {
-        &self.0 as *const _
+    pub fn downcast_ref<T: PhpClass>(&self) -> Option<&T> {
+        let object = self.as_object()?;
+        object.downcast_ref::<T>().map(|v| unsafe { &*(v as *const T) })
     }
 
+    /// Mutable Rust value, borrowed along with the zval holding the object
     #[inline]
-    pub fn as_raw_mut(&mut self) -> *mut zend::Zval {
-        &mut self.0 as *mut _
+    pub fn downcast_mut<T: PhpClass>(&mut self) -> Option<&mut T> {
+        let mut object = self.as_object()?;
+        unsafe { object.downcast_mut::<T>() }.map(|v| unsafe { &mut *(v as *mut T) })
     }
-}
 
-impl ArrayApi for Zval {
+    /// ZVAL_DEREF: the referenced value if this zval is a reference, self otherwise
     #[inline]
-    fn get<'a, I>(&self, index: I) -> Option<&Zval>
-    where
-        I: Into<ArrayIndex<'a>>
-    {
-        self.as_array()
-            .expect("Can't convert Zval to Array because it is not array")
-            .get(index)
-            .map(|zv| unsafe { &*(zv as *const _) })
+    pub fn dereference_mut(&mut self) -> &mut Zval {
+        unsafe {
+            if self.0.get_type() as zend::Type == zend::IS_REFERENCE {
+                (*self.0.value.ref_).val.as_safe_mut()
+            } else {
+                self
+            }
+        }
     }
 
+    /// PHP comparison, like `$a <=> $b`
     #[inline]
-    fn insert<'a, I, V>(&mut self, index: I, val: V) -> Option<&Zval>
-    where
-        I: Into<ArrayIndex<'a>>,
-        V: Into<Zval>,
-    {
-        self.as_array()
-            .expect("Can't convert Zval to Array because it is not array")
-            .insert(index, val)
-            .map(|zv| unsafe { &*(zv as *const _) })
+    pub fn compare(&self, other: &Zval) -> Ordering {
+        unsafe { zend::Zval::compare(self.as_raw() as *mut _, other.as_raw() as *mut _).cmp(&0) }
     }
 
     #[inline]
-    fn exists<'a, I>(&self, index: I) -> bool
-    where
-        I: Into<ArrayIndex<'a>>
-    {
-        self.as_array()
-            .expect("Can't convert Zval to Array because it is not array")
-            .exists(index)
+    pub fn raw(self) -> zend::Zval {
+        self.0
     }
 
     #[inline]
-    fn buckets_iter(&self) -> BucketsIter {
-        let raw_array = self.as_array()
-            .expect("Can't convert Zval to Array because it is not array")
-            .raw();
-        unsafe { BucketsIter::from_raw(raw_array) }
+    pub fn as_raw(&self) -> *const zend::Zval {
+        &self.0 as *const _
+    }
+
+    #[inline]
+    pub fn as_raw_mut(&mut self) -> *mut zend::Zval {
+        &mut self.0 as *mut _
+    }
+}
+
+/// Panics if the value is not an array
+impl ArrayApi for Zval {
+    #[inline]
+    fn as_array_ref(&self) -> &ZArrayRef {
+        let arr = self.as_array()
+            .expect("Can't convert Zval to Array because it is not array");
+        unsafe { ZArrayRef::from_ptr(arr.raw()) }
     }
 
+    /// SEPARATE_ARRAY: a shared array is replaced with a copy owned by this zval
     #[inline]
-    fn buckets_iter_mut(&mut self) -> BucketsIterMut {
-        let raw_array = self.as_array()
+    fn as_array_mut(&mut self) -> &mut ZArrayRef {
+        let ht = self.as_array()
             .expect("Can't convert Zval to Array because it is not array")
             .raw();
-        unsafe { BucketsIterMut::from_raw(raw_array) }
+        unsafe {
+            if (*ht).gc.refcount > 1 || (*ht).is_immutable() {
+                let dup = zend::HashTable::array_dup(ht);
+                if !(*ht).is_immutable() {
+                    (*ht).gc.refcount -= 1;
+                }
+                self.0.set_arr(&mut *dup);
+                ZArrayRef::from_ptr_mut(dup)
+            } else {
+                ZArrayRef::from_ptr_mut(ht)
+            }
+        }
     }
 }
 
@@ -151,6 +175,52 @@ impl DerefMut for Zval {
     }
 }
 
+/// Zval holding a reference to its value, released on drop like zval_ptr_dtor
+#[repr(transparent)]
+pub struct OwnedZval(Zval);
+
+impl OwnedZval {
+    /// # Safety
+    /// `zv` must hold a reference the caller gives up
+    #[inline]
+    pub unsafe fn new(zv: Zval) -> Self {
+        OwnedZval(zv)
+    }
+}
+
+impl Deref for OwnedZval {
+    type Target = Zval;
+
+    #[inline]
+    fn deref(&self) -> &Zval {
+        &self.0
+    }
+}
+
+impl DerefMut for OwnedZval {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Zval {
+        &mut self.0
+    }
+}
+
+impl Drop for OwnedZval {
+    #[inline]
+    fn drop(&mut self) {
+        zend::Zval::ptr_dtor(self.0.as_raw_mut());
+    }
+}
+
+/// The zval takes over the reference
+impl IntoZval for OwnedZval {
+    #[inline]
+    fn into_zval(self) -> Zval {
+        let zv = self.0;
+        mem::forget(self);
+        zv
+    }
+}
+
 impl PartialEq for Zval {
     fn eq(&self, other: &Zval) -> bool {
         self.value() == other.value()
@@ -181,6 +251,19 @@ impl From<&'_ str> for Zval {
     }
 }
 
+/// Binary-safe string
+impl From<&'_ [u8]> for Zval {
+    fn from(from: &'_ [u8]) -> Self {
+        ZendStr::from_bytes(from).into_zval()
+    }
+}
+
+impl From<Vec<u8>> for Zval {
+    fn from(from: Vec<u8>) -> Self {
+        Zval::from(from.as_slice())
+    }
+}
+
 impl ToSafe for zend::Zval {
     type SafeType = Zval;
 }
\ No newline at end of file
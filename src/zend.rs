@@ -3,20 +3,44 @@
 
 pub use self::api::*;
 pub use self::alloc::*;
+pub use self::class::*;
 pub use self::compile::*;
+pub use self::errors::*;
+pub use self::exceptions::*;
+pub use self::globals::*;
 pub use self::hash::*;
+pub use self::info::*;
+pub use self::ini::*;
 pub use self::types::*;
 pub use self::string::*;
+pub use self::tsrm::*;
 pub use self::module::*;
+pub use self::object_handlers::*;
+pub use self::objects::*;
 pub use self::portability::*;
+pub use self::sapi::*;
 pub use self::variables::*;
 
 pub mod api;
 pub mod alloc;
+pub mod class;
 pub mod compile;
+pub mod errors;
+pub mod exceptions;
+pub mod globals;
 pub mod hash;
+pub mod info;
+pub mod ini;
 pub mod types;
 pub mod string;
+pub mod tsrm;
 pub mod module;
+pub mod object_handlers;
+pub mod objects;
+pub mod operators;
 pub mod portability;
-pub mod variables;
\ No newline at end of file
+pub mod sapi;
+pub mod variables;
+
+#[cfg(all(test, php_bindings))]
+mod bindings;
\ No newline at end of file
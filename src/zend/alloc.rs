@@ -1,15 +1,78 @@
 use std::mem;
 use std::os::raw::c_void;
+#[cfg(php_debug)]
+use std::{cell::RefCell, collections::{BTreeMap, HashMap}, ffi::CString, os::raw::c_char, panic::Location, sync::Mutex};
 use crate::zend::{zend_type, HashTable};
 
+// Debug builds take ZEND_FILE_LINE_DC and ZEND_FILE_LINE_ORIG_DC arguments
 extern "C" {
-    fn _emalloc(size: usize) -> *mut c_void;
-    fn _safe_emalloc(nmemb: usize, size: usize, offset: usize) -> *mut c_void;
+    fn _emalloc(
+        size: usize,
+        #[cfg(php_debug)] __zend_filename: *const c_char,
+        #[cfg(php_debug)] __zend_lineno: u32,
+        #[cfg(php_debug)] __zend_orig_filename: *const c_char,
+        #[cfg(php_debug)] __zend_orig_lineno: u32,
+    ) -> *mut c_void;
+    fn _safe_emalloc(
+        nmemb: usize,
+        size: usize,
+        offset: usize,
+        #[cfg(php_debug)] __zend_filename: *const c_char,
+        #[cfg(php_debug)] __zend_lineno: u32,
+        #[cfg(php_debug)] __zend_orig_filename: *const c_char,
+        #[cfg(php_debug)] __zend_orig_lineno: u32,
+    ) -> *mut c_void;
+    fn _ecalloc(
+        nmemb: usize,
+        size: usize,
+        #[cfg(php_debug)] __zend_filename: *const c_char,
+        #[cfg(php_debug)] __zend_lineno: u32,
+        #[cfg(php_debug)] __zend_orig_filename: *const c_char,
+        #[cfg(php_debug)] __zend_orig_lineno: u32,
+    ) -> *mut c_void;
+    fn _erealloc(
+        ptr: *mut c_void,
+        size: usize,
+        #[cfg(php_debug)] __zend_filename: *const c_char,
+        #[cfg(php_debug)] __zend_lineno: u32,
+        #[cfg(php_debug)] __zend_orig_filename: *const c_char,
+        #[cfg(php_debug)] __zend_orig_lineno: u32,
+    ) -> *mut c_void;
     fn _safe_malloc(nmemb: usize, size: usize, offset: usize) -> *mut c_void;
-    fn _efree(ptr: *mut c_void);
+    fn _efree(
+        ptr: *mut c_void,
+        #[cfg(php_debug)] __zend_filename: *const c_char,
+        #[cfg(php_debug)] __zend_lineno: u32,
+        #[cfg(php_debug)] __zend_orig_filename: *const c_char,
+        #[cfg(php_debug)] __zend_orig_lineno: u32,
+    );
     fn __zend_malloc(len: usize) -> *mut c_void;
 }
 
+/// ZEND_FILE_LINE_CC: location of the Rust caller, reported by the debug allocator on leaks
+#[cfg(php_debug)]
+#[track_caller]
+#[inline]
+pub fn zend_file_line() -> (*const c_char, u32) {
+    static FILES: Mutex<BTreeMap<&'static str, CString>> = Mutex::new(BTreeMap::new());
+    thread_local! {
+        /// Interned names already seen by this thread, allocations don't take the lock
+        static CACHE: RefCell<HashMap<&'static str, *const c_char>> = RefCell::new(HashMap::new());
+    }
+
+    let location = Location::caller();
+    let file = CACHE.with(|cache| {
+        *cache.borrow_mut().entry(location.file()).or_insert_with(|| {
+            let mut files = FILES.lock().unwrap_or_else(|err| err.into_inner());
+            // CString data stays in place when the map moves it, and is never removed
+            files.entry(location.file())
+                .or_insert_with(|| CString::new(location.file()).unwrap_or_default())
+                .as_ptr()
+        })
+    });
+    (file, location.line())
+}
+
 pub const ZEND_MM_ALIGNMENT: zend_type = 8;
 pub const ZEND_MM_ALIGNMENT_LOG2: zend_type = 3;
 pub const ZEND_MM_ALIGNMENT_MASK: zend_type = !(ZEND_MM_ALIGNMENT - 1);
@@ -17,24 +80,93 @@ pub const fn ZEND_MM_ALIGNED_SIZE(size: usize) -> zend_type {
     (size + ZEND_MM_ALIGNMENT - 1) & ZEND_MM_ALIGNMENT_MASK
 }
 
+#[track_caller]
 #[inline]
 pub fn emalloc<T>(size: usize) -> *mut T {
-    unsafe { _emalloc(size) as *mut _ }
+    #[cfg(php_debug)]
+    let (file, line) = zend_file_line();
+    unsafe {
+        _emalloc(
+            size,
+            #[cfg(php_debug)] file,
+            #[cfg(php_debug)] line,
+            #[cfg(php_debug)] std::ptr::null(),
+            #[cfg(php_debug)] 0,
+        ) as *mut _
+    }
 }
 
+#[track_caller]
 #[inline]
 pub fn safe_emalloc<T>(nmemb: usize, size: usize, offset: usize) -> *mut T {
-    unsafe { _safe_emalloc(nmemb, size, offset) as *mut _ }
+    #[cfg(php_debug)]
+    let (file, line) = zend_file_line();
+    unsafe {
+        _safe_emalloc(
+            nmemb,
+            size,
+            offset,
+            #[cfg(php_debug)] file,
+            #[cfg(php_debug)] line,
+            #[cfg(php_debug)] std::ptr::null(),
+            #[cfg(php_debug)] 0,
+        ) as *mut _
+    }
 }
 
+#[track_caller]
+#[inline]
+pub fn ecalloc<T>(nmemb: usize, size: usize) -> *mut T {
+    #[cfg(php_debug)]
+    let (file, line) = zend_file_line();
+    unsafe {
+        _ecalloc(
+            nmemb,
+            size,
+            #[cfg(php_debug)] file,
+            #[cfg(php_debug)] line,
+            #[cfg(php_debug)] std::ptr::null(),
+            #[cfg(php_debug)] 0,
+        ) as *mut _
+    }
+}
+
+#[track_caller]
+#[inline]
+pub fn erealloc<T>(ptr: *mut T, size: usize) -> *mut T {
+    #[cfg(php_debug)]
+    let (file, line) = zend_file_line();
+    unsafe {
+        _erealloc(
+            ptr as *mut _,
+            size,
+            #[cfg(php_debug)] file,
+            #[cfg(php_debug)] line,
+            #[cfg(php_debug)] std::ptr::null(),
+            #[cfg(php_debug)] 0,
+        ) as *mut _
+    }
+}
+
+#[track_caller]
 #[inline]
 pub fn efree<T>(ptr: *mut T) {
-    unsafe { _efree(ptr as *mut _) }
+    #[cfg(php_debug)]
+    let (file, line) = zend_file_line();
+    unsafe {
+        _efree(
+            ptr as *mut _,
+            #[cfg(php_debug)] file,
+            #[cfg(php_debug)] line,
+            #[cfg(php_debug)] std::ptr::null(),
+            #[cfg(php_debug)] 0,
+        )
+    }
 }
 
 
 /** Selective persistent/non persistent allocation */
-
+#[track_caller]
 #[inline]
 pub fn pemalloc<T>(size: usize, persistent: bool) -> *mut T {
     if persistent {
@@ -44,6 +176,7 @@ pub fn pemalloc<T>(size: usize, persistent: bool) -> *mut T {
     }
 }
 
+#[track_caller]
 #[inline]
 pub fn safe_pemalloc<T>(nmemb: usize, size: usize, offset: usize, persistent: bool) -> *mut T {
     if persistent {
@@ -53,6 +186,7 @@ pub fn safe_pemalloc<T>(nmemb: usize, size: usize, offset: usize, persistent: bo
     }
 }
 
+#[track_caller]
 #[inline]
 pub fn pefree<T>(ptr: *mut T, persistent: bool) {
     if persistent {
@@ -62,6 +196,7 @@ pub fn pefree<T>(ptr: *mut T, persistent: bool) {
     }
 }
 
+#[track_caller]
 #[inline]
 pub fn pefree_size<T>(ptr: *mut T, size: usize, persistent: bool) {
     if persistent {
@@ -71,6 +206,7 @@ pub fn pefree_size<T>(ptr: *mut T, size: usize, persistent: bool) {
     }
 }
 
+#[track_caller]
 #[inline]
 pub fn efree_size<T>(ptr: *mut T, _size: usize) {
     efree(ptr as *mut _)
@@ -79,12 +215,14 @@ pub fn efree_size<T>(ptr: *mut T, _size: usize) {
 /// fast cache for HashTables
 impl HashTable {
     /// ALLOC_HASHTABLE
+    #[track_caller]
     #[inline]
     pub fn alloc() -> *mut Self {
         emalloc(mem::size_of::<Self>())
     }
 
     /// FREE_HASHTABLE
+    #[track_caller]
     #[inline]
     pub fn free(ht: *mut Self) {
         efree_size(ht, mem::size_of::<Self>())
@@ -1,7 +1,8 @@
 use std::mem;
 use std::os::raw::{c_char, c_int};
 use crate::zend::{
-    zval, zend_execute_data, zend_internal_arg_info, ZEND_RESULT_CODE,
+    zval, zend_long, zend_bool, zend_string, zend_execute_data, zend_internal_arg_info, zend_class_entry,
+    ZEND_RESULT_CODE,
 };
 
 pub type FunctionEntry = zend_function_entry;
@@ -27,6 +28,45 @@ extern {
 
 /* End of parameter parsing API */
 
+extern "C" {
+    pub fn zend_register_internal_class(class_entry: *mut zend_class_entry) -> *mut zend_class_entry;
+    pub fn zend_register_internal_class_ex(class_entry: *mut zend_class_entry, parent_ce: *mut zend_class_entry) -> *mut zend_class_entry;
+    pub fn zend_register_internal_interface(orig_class_entry: *mut zend_class_entry) -> *mut zend_class_entry;
+    pub fn zend_class_implements(class_entry: *mut zend_class_entry, num_interfaces: c_int, ...);
+
+    pub fn zend_declare_property(ce: *mut zend_class_entry, name: *const c_char, name_length: usize, property: *mut zval, access_type: c_int) -> c_int;
+    pub fn zend_declare_property_null(ce: *mut zend_class_entry, name: *const c_char, name_length: usize, access_type: c_int) -> c_int;
+    pub fn zend_declare_property_bool(ce: *mut zend_class_entry, name: *const c_char, name_length: usize, value: zend_long, access_type: c_int) -> c_int;
+    pub fn zend_declare_property_long(ce: *mut zend_class_entry, name: *const c_char, name_length: usize, value: zend_long, access_type: c_int) -> c_int;
+    pub fn zend_declare_property_double(ce: *mut zend_class_entry, name: *const c_char, name_length: usize, value: f64, access_type: c_int) -> c_int;
+    pub fn zend_declare_property_stringl(ce: *mut zend_class_entry, name: *const c_char, name_length: usize, value: *const c_char, value_len: usize, access_type: c_int) -> c_int;
+
+    pub fn zend_declare_class_constant(ce: *mut zend_class_entry, name: *const c_char, name_length: usize, value: *mut zval) -> c_int;
+    pub fn zend_declare_class_constant_null(ce: *mut zend_class_entry, name: *const c_char, name_length: usize) -> c_int;
+    pub fn zend_declare_class_constant_long(ce: *mut zend_class_entry, name: *const c_char, name_length: usize, value: zend_long) -> c_int;
+    pub fn zend_declare_class_constant_bool(ce: *mut zend_class_entry, name: *const c_char, name_length: usize, value: zend_bool) -> c_int;
+    pub fn zend_declare_class_constant_double(ce: *mut zend_class_entry, name: *const c_char, name_length: usize, value: f64) -> c_int;
+    pub fn zend_declare_class_constant_stringl(ce: *mut zend_class_entry, name: *const c_char, name_length: usize, value: *const c_char, value_length: usize) -> c_int;
+
+    pub fn zend_lookup_class(name: *mut zend_string) -> *mut zend_class_entry;
+    #[cfg(not(feature = "php80"))]
+    pub fn instanceof_function_ex(instance_ce: *const zend_class_entry, ce: *const zend_class_entry, interfaces_only: zend_bool) -> zend_bool;
+    #[cfg(feature = "php80")]
+    pub fn instanceof_function_slow(instance_ce: *const zend_class_entry, ce: *const zend_class_entry) -> bool;
+}
+
+/// instanceof_function: `instance_ce` is `ce`, extends or implements it
+///
+/// # Safety
+/// Both pointers must point to valid class entries
+#[inline]
+pub unsafe fn instanceof_function(instance_ce: *const zend_class_entry, ce: *const zend_class_entry) -> bool {
+    #[cfg(not(feature = "php80"))]
+    return instanceof_function_ex(instance_ce, ce, 0) != 0;
+    #[cfg(feature = "php80")]
+    return instance_ce == ce || instanceof_function_slow(instance_ce, ce);
+}
+
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -0,0 +1,41 @@
+//! Compares the handwritten layouts with the ones bindgen generated from the PHP headers,
+//! see build.rs
+
+use std::mem;
+
+#[allow(non_upper_case_globals, non_camel_case_types, non_snake_case, dead_code, improper_ctypes)]
+mod sys {
+    include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+}
+
+macro_rules! assert_same_layout {
+    ($($ours:ty => $theirs:ty),* $(,)?) => {
+        $(
+            assert_eq!(
+                mem::size_of::<$ours>(), mem::size_of::<$theirs>(), concat!("Size of: ", stringify!($ours))
+            );
+            assert_eq!(
+                mem::align_of::<$ours>(), mem::align_of::<$theirs>(), concat!("Alignment of ", stringify!($ours))
+            );
+        )*
+    };
+}
+
+#[test]
+fn layouts_match_headers() {
+    assert_same_layout! {
+        super::zval => sys::zval,
+        super::zend_string => sys::zend_string,
+        super::zend_array => sys::zend_array,
+        super::Bucket => sys::Bucket,
+        super::zend_object => sys::zend_object,
+        super::zend_execute_data => sys::zend_execute_data,
+        super::zend_class_entry => sys::zend_class_entry,
+        super::zend_object_handlers => sys::zend_object_handlers,
+        super::Module => sys::zend_module_entry,
+        super::zend_ini_entry_def => sys::zend_ini_entry_def,
+        super::zend_ini_entry => sys::zend_ini_entry,
+        super::sapi_module_struct => sys::sapi_module_struct,
+        super::zend_internal_arg_info => sys::zend_internal_arg_info,
+    }
+}
@@ -0,0 +1,216 @@
+use std::mem;
+use std::os::raw::{c_char, c_int, c_uchar, c_void};
+use crate::zend::{
+    zend_string, zend_object, zend_function, zend_function_entry, zval, HashTable,
+};
+#[cfg(feature = "php83")]
+use crate::zend::zend_object_handlers;
+
+pub type ClassEntry = zend_class_entry;
+
+/// Opaque types, only used behind pointers
+pub enum zend_object_iterator {}
+pub enum zend_object_iterator_funcs {}
+pub enum zend_serialize_data {}
+pub enum zend_unserialize_data {}
+pub enum zend_trait_alias {}
+pub enum zend_trait_precedence {}
+pub enum zend_property_info {}
+pub enum zend_class_arrayaccess_funcs {}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct zend_class_name {
+    pub name: *mut zend_string,
+    pub lc_name: *mut zend_string,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct zend_class_iterator_funcs {
+    #[cfg(not(feature = "php74"))]
+    pub funcs: *mut zend_object_iterator_funcs,
+    pub zf_new_iterator: *mut zend_function,
+    pub zf_valid: *mut zend_function,
+    pub zf_current: *mut zend_function,
+    pub zf_key: *mut zend_function,
+    pub zf_next: *mut zend_function,
+    pub zf_rewind: *mut zend_function,
+}
+
+pub type create_object_t = Option<unsafe extern "C" fn(class_type: *mut zend_class_entry) -> *mut zend_object>;
+
+/// Fields are added by PHP 7.3/7.4 (map pointers, `__serialize`, trait names), 8.0 (attributes),
+/// 8.1 (mutable data, enums), 8.2 (ArrayAccess funcs) and 8.3 (default object handlers)
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct zend_class_entry {
+    pub type_: c_char,
+    pub name: *mut zend_string,
+    /// class entry, or the parent name before the class is linked since PHP 7.4
+    pub parent: *mut zend_class_entry,
+    pub refcount: c_int,
+    pub ce_flags: u32,
+
+    pub default_properties_count: c_int,
+    pub default_static_members_count: c_int,
+    pub default_properties_table: *mut zval,
+    pub default_static_members_table: *mut zval,
+    #[cfg(not(feature = "php74"))]
+    pub static_members_table: *mut zval,
+    #[cfg(feature = "php74")]
+    pub static_members_table__ptr: *mut *mut zval,
+    pub function_table: HashTable,
+    pub properties_info: HashTable,
+    pub constants_table: HashTable,
+
+    #[cfg(feature = "php81")]
+    pub mutable_data__ptr: *mut c_void,
+    #[cfg(feature = "php81")]
+    pub inheritance_cache: *mut c_void,
+
+    #[cfg(feature = "php74")]
+    pub properties_info_table: *mut *mut zend_property_info,
+
+    pub constructor: *mut zend_function,
+    pub destructor: *mut zend_function,
+    pub clone: *mut zend_function,
+    pub __get: *mut zend_function,
+    pub __set: *mut zend_function,
+    pub __unset: *mut zend_function,
+    pub __isset: *mut zend_function,
+    pub __call: *mut zend_function,
+    pub __callstatic: *mut zend_function,
+    pub __tostring: *mut zend_function,
+    pub __debugInfo: *mut zend_function,
+    #[cfg(not(feature = "php74"))]
+    pub serialize_func: *mut zend_function,
+    #[cfg(not(feature = "php74"))]
+    pub unserialize_func: *mut zend_function,
+    #[cfg(feature = "php74")]
+    pub __serialize: *mut zend_function,
+    #[cfg(feature = "php74")]
+    pub __unserialize: *mut zend_function,
+
+    #[cfg(feature = "php83")]
+    pub default_object_handlers: *const zend_object_handlers,
+
+    #[cfg(not(feature = "php74"))]
+    pub iterator_funcs: zend_class_iterator_funcs,
+    /// allocated only if the class implements Iterator or IteratorAggregate
+    #[cfg(feature = "php74")]
+    pub iterator_funcs_ptr: *mut zend_class_iterator_funcs,
+    /// allocated only if the class implements ArrayAccess
+    #[cfg(feature = "php82")]
+    pub arrayaccess_funcs_ptr: *mut zend_class_arrayaccess_funcs,
+
+    /* handlers */
+    /// shares its slot with `interface_gets_implemented` since PHP 7.3
+    pub create_object: create_object_t,
+    pub get_iterator: Option<unsafe extern "C" fn(ce: *mut zend_class_entry, object: *mut zval, by_ref: c_int) -> *mut zend_object_iterator>,
+    /// a class implements this interface
+    #[cfg(not(feature = "php74"))]
+    pub interface_gets_implemented: Option<unsafe extern "C" fn(iface: *mut zend_class_entry, class_type: *mut zend_class_entry) -> c_int>,
+    pub get_static_method: Option<unsafe extern "C" fn(ce: *mut zend_class_entry, method: *mut zend_string) -> *mut zend_function>,
+
+    /* serializer callbacks */
+    pub serialize: Option<unsafe extern "C" fn(object: *mut zval, buffer: *mut *mut c_uchar, buf_len: *mut usize, data: *mut zend_serialize_data) -> c_int>,
+    pub unserialize: Option<unsafe extern "C" fn(object: *mut zval, ce: *mut zend_class_entry, buf: *const c_uchar, buf_len: usize, data: *mut zend_unserialize_data) -> c_int>,
+
+    pub num_interfaces: u32,
+    pub num_traits: u32,
+    /// class entries, or the interface names before the class is linked since PHP 7.4
+    pub interfaces: *mut *mut zend_class_entry,
+
+    #[cfg(not(feature = "php74"))]
+    pub traits: *mut *mut zend_class_entry,
+    #[cfg(feature = "php74")]
+    pub trait_names: *mut zend_class_name,
+    pub trait_aliases: *mut *mut zend_trait_alias,
+    pub trait_precedences: *mut *mut zend_trait_precedence,
+    #[cfg(feature = "php80")]
+    pub attributes: *mut HashTable,
+
+    #[cfg(feature = "php81")]
+    pub enum_backing_type: u32,
+    #[cfg(feature = "php81")]
+    pub backed_enum_table: *mut HashTable,
+
+    pub info: zend_class_entry_info,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union zend_class_entry_info {
+    pub user: zend_class_entry_info_user,
+    pub internal: zend_class_entry_info_internal,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct zend_class_entry_info_user {
+    pub filename: *mut zend_string,
+    pub line_start: u32,
+    pub line_end: u32,
+    pub doc_comment: *mut zend_string,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct zend_class_entry_info_internal {
+    pub builtin_functions: *const zend_function_entry,
+    pub module: *mut c_void, // todo: use zend_module_entry instead of c_void
+}
+
+impl Default for zend_class_entry {
+    fn default() -> Self {
+        unsafe { mem::zeroed() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zend_class_entry_layout() {
+        let size = if cfg!(feature = "php83") {
+            512
+        } else if cfg!(feature = "php82") {
+            504
+        } else if cfg!(feature = "php81") {
+            496
+        } else if cfg!(feature = "php80") {
+            464
+        } else if cfg!(feature = "php74") {
+            456
+        } else {
+            504
+        };
+        assert_eq!(
+            mem::size_of::<zend_class_entry>(), size, concat!("Size of: ", stringify!(zend_class_entry))
+        );
+        assert_eq!(
+            mem::align_of::<zend_class_entry>(), 8, concat!("Alignment of ", stringify!(zend_class_entry))
+        );
+    }
+
+    #[test]
+    fn zend_class_entry_offsets() {
+        let create_object = if cfg!(feature = "php83") {
+            384
+        } else if cfg!(feature = "php82") {
+            376
+        } else if cfg!(feature = "php81") {
+            368
+        } else if cfg!(feature = "php74") {
+            352
+        } else {
+            392
+        };
+        let ce = mem::MaybeUninit::<zend_class_entry>::uninit();
+        let base = ce.as_ptr();
+        let offset = unsafe { std::ptr::addr_of!((*base).create_object) as usize - base as usize };
+        assert_eq!(offset, create_object, "Offset of: zend_class_entry::create_object");
+    }
+}
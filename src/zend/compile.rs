@@ -1,7 +1,9 @@
 use std::mem;
 use std::os::raw::{c_void, c_int, c_char};
+#[cfg(not(feature = "php80"))]
+use crate::zend::zend_bool;
 use crate::zend::{
-    zend_uchar, zend_bool, zval, zend_string, zend_array, HashTable, zif_handler,
+    zend_uchar, zval, zend_string, zend_array, zend_class_entry, HashTable, zif_handler,
     ZEND_MAX_RESERVED_RESOURCES
 };
 
@@ -9,6 +11,99 @@ pub type ExecuteData = zend_execute_data;
 pub type Function = zend_function;
 pub type FunctionCommon = zend_function_common;
 
+/** method flags (types) */
+#[cfg(not(feature = "php74"))]
+pub const ZEND_ACC_STATIC: u32 = 0x01;
+#[cfg(not(feature = "php74"))]
+pub const ZEND_ACC_ABSTRACT: u32 = 0x02;
+#[cfg(not(feature = "php74"))]
+pub const ZEND_ACC_FINAL: u32 = 0x04;
+#[cfg(not(feature = "php74"))]
+pub const ZEND_ACC_IMPLEMENTED_ABSTRACT: u32 = 0x08;
+
+/** class flags (types) */
+#[cfg(not(feature = "php74"))]
+pub const ZEND_ACC_IMPLICIT_ABSTRACT_CLASS: u32 = 0x10;
+#[cfg(not(feature = "php74"))]
+pub const ZEND_ACC_EXPLICIT_ABSTRACT_CLASS: u32 = 0x20;
+#[cfg(not(feature = "php74"))]
+pub const ZEND_ACC_INTERFACE: u32 = 0x40;
+#[cfg(not(feature = "php74"))]
+pub const ZEND_ACC_TRAIT: u32 = 0x80;
+
+/** method flags (visibility) */
+#[cfg(not(feature = "php74"))]
+pub const ZEND_ACC_PUBLIC: u32 = 0x100;
+#[cfg(not(feature = "php74"))]
+pub const ZEND_ACC_PROTECTED: u32 = 0x200;
+#[cfg(not(feature = "php74"))]
+pub const ZEND_ACC_PRIVATE: u32 = 0x400;
+pub const ZEND_ACC_PPP_MASK: u32 = ZEND_ACC_PUBLIC | ZEND_ACC_PROTECTED | ZEND_ACC_PRIVATE;
+
+/** method flags (special method detection) */
+#[cfg(not(feature = "php74"))]
+pub const ZEND_ACC_CTOR: u32 = 0x2000;
+#[cfg(not(feature = "php74"))]
+pub const ZEND_ACC_DTOR: u32 = 0x4000;
+#[cfg(not(feature = "php74"))]
+pub const ZEND_ACC_CLONE: u32 = 0x8000;
+
+/// method flag, deprecation flag
+#[cfg(not(feature = "php74"))]
+pub const ZEND_ACC_DEPRECATED: u32 = 0x40000;
+/// class has magic methods __get/__set/__unset/__isset that use guards
+#[cfg(not(feature = "php74"))]
+pub const ZEND_ACC_USE_GUARDS: u32 = 0x1000000;
+/// function has variadic arguments
+#[cfg(not(feature = "php74"))]
+pub const ZEND_ACC_VARIADIC: u32 = 0x1000000;
+/// function returns by reference
+#[cfg(not(feature = "php74"))]
+pub const ZEND_ACC_RETURN_REFERENCE: u32 = 0x4000000;
+
+// PHP 7.4 renumbered the flags, class and function flags share bits above the common ones
+
+/** common flags (visibility, properties, methods and constants) */
+#[cfg(feature = "php74")]
+pub const ZEND_ACC_PUBLIC: u32 = 1 << 0;
+#[cfg(feature = "php74")]
+pub const ZEND_ACC_PROTECTED: u32 = 1 << 1;
+#[cfg(feature = "php74")]
+pub const ZEND_ACC_PRIVATE: u32 = 1 << 2;
+#[cfg(feature = "php74")]
+pub const ZEND_ACC_STATIC: u32 = 1 << 4;
+#[cfg(feature = "php74")]
+pub const ZEND_ACC_FINAL: u32 = 1 << 5;
+#[cfg(feature = "php74")]
+pub const ZEND_ACC_ABSTRACT: u32 = 1 << 6;
+
+/** class flags */
+#[cfg(feature = "php74")]
+pub const ZEND_ACC_INTERFACE: u32 = 1 << 0;
+#[cfg(feature = "php74")]
+pub const ZEND_ACC_TRAIT: u32 = 1 << 1;
+#[cfg(feature = "php74")]
+pub const ZEND_ACC_IMPLICIT_ABSTRACT_CLASS: u32 = 1 << 4;
+#[cfg(feature = "php74")]
+pub const ZEND_ACC_EXPLICIT_ABSTRACT_CLASS: u32 = ZEND_ACC_ABSTRACT;
+/// class has magic methods __get/__set/__unset/__isset that use guards
+#[cfg(feature = "php74")]
+pub const ZEND_ACC_USE_GUARDS: u32 = 1 << 11;
+
+/** function flags */
+/// method flag, deprecation flag
+#[cfg(feature = "php74")]
+pub const ZEND_ACC_DEPRECATED: u32 = 1 << 11;
+/// function returns by reference
+#[cfg(feature = "php74")]
+pub const ZEND_ACC_RETURN_REFERENCE: u32 = 1 << 12;
+/// function has variadic arguments
+#[cfg(feature = "php74")]
+pub const ZEND_ACC_VARIADIC: u32 = 1 << 14;
+
+pub const ZEND_INTERNAL_CLASS: zend_uchar = 1;
+pub const ZEND_USER_CLASS: zend_uchar = 2;
+
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub struct zend_execute_data {
@@ -21,18 +116,36 @@ pub struct zend_execute_data {
     pub func: *mut zend_function,
     /// this + call_info + num_args
     pub This: zval,
+    /// moved into `This` since PHP 7.1
+    #[cfg(not(feature = "php74"))]
+    pub called_scope: *mut zend_class_entry,
     pub prev_execute_data: *mut zend_execute_data,
     pub symbol_table: *mut zend_array,
     /// cache op_array.run_time_cache
     pub run_time_cache: *mut *mut c_void,
+    /// cache op_array.literals
+    #[cfg(not(feature = "php74"))]
     pub literals: *mut zval,
+    /// named arguments not matching a parameter, collected by variadics
+    #[cfg(feature = "php80")]
+    pub extra_named_params: *mut zend_array,
 }
 
+/// Number of zval slots taken by zend_execute_data at the start of a call frame
+pub const ZEND_CALL_FRAME_SLOT: usize =
+    mem::size_of::<zend_execute_data>().div_ceil(mem::size_of::<zval>());
+
 impl zend_execute_data {
     #[inline]
     pub unsafe fn num_args(&self) -> u32 {
         self.This.u2.num_args
     }
+
+    /// ZEND_CALL_ARG, `n` starts from 1
+    #[inline]
+    pub unsafe fn arg(&self, n: u32) -> *mut zval {
+        (self as *const Self as *mut zval).add(ZEND_CALL_FRAME_SLOT + n as usize - 1)
+    }
 }
 
 #[repr(C)]
@@ -76,7 +189,7 @@ pub struct zend_internal_function {
     pub arg_flags: [zend_uchar; 3usize],
     pub fn_flags: u32,
     pub function_name: *mut zend_string,
-    pub scope: *mut c_void, // todo: use zend_class_entry instead of c_void
+    pub scope: *mut zend_class_entry,
     pub prototype: *mut zend_function,
     pub num_args: u32,
     pub required_num_args: u32,
@@ -107,13 +220,38 @@ pub struct zend_function_common {
     pub arg_flags: [zend_uchar; 3usize],
     pub fn_flags: u32,
     pub function_name: *mut zend_string,
-    pub scope: *mut c_void, // todo: use zend_class_entry instead of c_void
+    pub scope: *mut zend_class_entry,
     pub prototype: *mut zend_function,
     pub num_args: u32,
     pub required_num_args: u32,
     pub arg_info: *mut zend_arg_info,
 }
 
+/// zend_type of an argument: an encoded type code or class name pointer in PHP 7.4,
+/// a pointer and a type mask holding the argument flags since PHP 8.0.
+/// Named apart from the `zend_type` integer alias.
+#[cfg(all(feature = "php74", not(feature = "php80")))]
+pub type zend_arg_type = usize;
+
+#[cfg(feature = "php80")]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct zend_arg_type {
+    pub ptr: *mut c_void,
+    pub type_mask: u32,
+}
+
+#[cfg(all(feature = "php80", not(feature = "php81")))]
+pub const _ZEND_TYPE_EXTRA_FLAGS_SHIFT: u32 = 24;
+#[cfg(feature = "php81")]
+pub const _ZEND_TYPE_EXTRA_FLAGS_SHIFT: u32 = 25;
+/// ZEND_SEND_BY_VAL/ZEND_SEND_BY_REF of the argument, the return value in the leading element
+#[cfg(feature = "php80")]
+pub const _ZEND_SEND_MODE_SHIFT: u32 = _ZEND_TYPE_EXTRA_FLAGS_SHIFT;
+#[cfg(feature = "php80")]
+pub const _ZEND_IS_VARIADIC_BIT: u32 = 1 << (_ZEND_TYPE_EXTRA_FLAGS_SHIFT + 2);
+
+#[cfg(not(feature = "php74"))]
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct zend_arg_info {
@@ -125,6 +263,25 @@ pub struct zend_arg_info {
     pub is_variadic: zend_bool,
 }
 
+#[cfg(all(feature = "php74", not(feature = "php80")))]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct zend_arg_info {
+    pub name: *mut zend_string,
+    pub type_: zend_arg_type,
+    pub pass_by_reference: zend_uchar,
+    pub is_variadic: zend_bool,
+}
+
+#[cfg(feature = "php80")]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct zend_arg_info {
+    pub name: *mut zend_string,
+    pub type_: zend_arg_type,
+    pub default_value: *mut zend_string,
+}
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct zend_op_array {
@@ -134,7 +291,7 @@ pub struct zend_op_array {
     pub arg_flags: [zend_uchar; 3usize],
     pub fn_flags: u32,
     pub function_name: *mut zend_string,
-    pub scope: *mut c_void, // todo: use zend_class_entry instead of c_void
+    pub scope: *mut zend_class_entry,
     pub prototype: *mut zend_function,
     pub num_args: u32,
     pub required_num_args: u32,
@@ -186,7 +343,9 @@ pub struct zend_try_catch_element {
     pub finally_end: u32,
 }
 
-/// arg_info for internal functions
+/// arg_info for internal functions, the leading element holds the number of required arguments
+/// in `name` (zend_internal_function_info)
+#[cfg(not(feature = "php74"))]
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct zend_internal_arg_info {
@@ -196,4 +355,55 @@ pub struct zend_internal_arg_info {
     pub pass_by_reference: zend_uchar,
     pub allow_null: zend_bool,
     pub is_variadic: zend_bool,
-}
\ No newline at end of file
+}
+
+#[cfg(all(feature = "php74", not(feature = "php80")))]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct zend_internal_arg_info {
+    pub name: *const c_char,
+    pub type_: zend_arg_type,
+    pub pass_by_reference: zend_uchar,
+    pub is_variadic: zend_bool,
+}
+
+/// Pass by reference and variadic flags are stored in `type_.type_mask`
+#[cfg(feature = "php80")]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct zend_internal_arg_info {
+    pub name: *const c_char,
+    pub type_: zend_arg_type,
+    pub default_value: *const c_char,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zend_execute_data_layout() {
+        let (size, frame_slot) = if cfg!(feature = "php80") {
+            (80, 5)
+        } else if cfg!(feature = "php74") {
+            (72, 5)
+        } else {
+            (88, 6)
+        };
+        assert_eq!(
+            mem::size_of::<zend_execute_data>(), size, concat!("Size of: ", stringify!(zend_execute_data))
+        );
+        assert_eq!(ZEND_CALL_FRAME_SLOT, frame_slot);
+    }
+
+    #[test]
+    fn zend_internal_arg_info_layout() {
+        let size = if cfg!(feature = "php80") { 32 } else { 24 };
+        assert_eq!(
+            mem::size_of::<zend_internal_arg_info>(), size, concat!("Size of: ", stringify!(zend_internal_arg_info))
+        );
+        assert_eq!(
+            mem::size_of::<zend_arg_info>(), size, concat!("Size of: ", stringify!(zend_arg_info))
+        );
+    }
+}
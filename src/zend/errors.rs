@@ -0,0 +1,26 @@
+use std::os::raw::{c_char, c_int};
+
+pub const E_ERROR: c_int = 1 << 0;
+pub const E_WARNING: c_int = 1 << 1;
+pub const E_PARSE: c_int = 1 << 2;
+pub const E_NOTICE: c_int = 1 << 3;
+pub const E_CORE_ERROR: c_int = 1 << 4;
+pub const E_CORE_WARNING: c_int = 1 << 5;
+pub const E_COMPILE_ERROR: c_int = 1 << 6;
+pub const E_COMPILE_WARNING: c_int = 1 << 7;
+pub const E_USER_ERROR: c_int = 1 << 8;
+pub const E_USER_WARNING: c_int = 1 << 9;
+pub const E_USER_NOTICE: c_int = 1 << 10;
+pub const E_STRICT: c_int = 1 << 11;
+pub const E_RECOVERABLE_ERROR: c_int = 1 << 12;
+pub const E_DEPRECATED: c_int = 1 << 13;
+pub const E_USER_DEPRECATED: c_int = 1 << 14;
+
+pub const E_ALL: c_int = E_ERROR | E_WARNING | E_PARSE | E_NOTICE | E_CORE_ERROR | E_CORE_WARNING
+    | E_COMPILE_ERROR | E_COMPILE_WARNING | E_USER_ERROR | E_USER_WARNING | E_USER_NOTICE
+    | E_RECOVERABLE_ERROR | E_DEPRECATED | E_USER_DEPRECATED | E_STRICT;
+pub const E_CORE: c_int = E_CORE_ERROR | E_CORE_WARNING;
+
+extern "C" {
+    pub fn zend_error(type_: c_int, format: *const c_char, ...);
+}
@@ -0,0 +1,22 @@
+use std::os::raw::c_char;
+use crate::zend::{zval, zend_long, zend_object, zend_class_entry};
+
+extern "C" {
+    pub static mut zend_ce_throwable: *mut zend_class_entry;
+    pub static mut zend_ce_exception: *mut zend_class_entry;
+    pub static mut zend_ce_error_exception: *mut zend_class_entry;
+    pub static mut zend_ce_error: *mut zend_class_entry;
+    pub static mut zend_ce_parse_error: *mut zend_class_entry;
+    pub static mut zend_ce_type_error: *mut zend_class_entry;
+    pub static mut zend_ce_arithmetic_error: *mut zend_class_entry;
+    pub static mut zend_ce_division_by_zero_error: *mut zend_class_entry;
+
+    pub fn zend_exception_get_default() -> *mut zend_class_entry;
+    pub fn zend_get_error_exception() -> *mut zend_class_entry;
+
+    pub fn zend_throw_exception(exception_ce: *mut zend_class_entry, message: *const c_char, code: zend_long) -> *mut zend_object;
+    pub fn zend_throw_exception_ex(exception_ce: *mut zend_class_entry, code: zend_long, format: *const c_char, ...) -> *mut zend_object;
+    pub fn zend_throw_exception_object(exception: *mut zval);
+    pub fn zend_throw_error(exception_ce: *mut zend_class_entry, format: *const c_char, ...);
+    pub fn zend_clear_exception();
+}
@@ -0,0 +1,103 @@
+use std::ptr;
+use std::os::raw::{c_int, c_void};
+use crate::zend::{zval, zend_array, zend_class_entry, zend_execute_data, HashTable};
+#[cfg(all(php_zts, feature = "php74"))]
+use crate::zend::tsrm_get_ls_cache;
+#[cfg(all(php_zts, not(feature = "php74")))]
+use crate::zend::{ts_rsrc_id, TSRMG_BULK};
+
+pub const SYMTABLE_CACHE_SIZE: usize = 32;
+
+/// Executor globals (EG), only the leading fields up to `fake_scope` are declared: the rest
+/// changes with every PHP version and build options. Never constructed from Rust.
+#[repr(C)]
+pub struct zend_executor_globals {
+    pub uninitialized_zval: zval,
+    pub error_zval: zval,
+    /* symbol table cache */
+    pub symtable_cache: [*mut zend_array; SYMTABLE_CACHE_SIZE],
+    pub symtable_cache_limit: *mut *mut zend_array,
+    pub symtable_cache_ptr: *mut *mut zend_array,
+    /// main symbol table
+    pub symbol_table: zend_array,
+    /// files already included
+    pub included_files: HashTable,
+    pub bailout: *mut c_void,
+    pub error_reporting: c_int,
+    pub exit_status: c_int,
+    /// function symbol table
+    pub function_table: *mut HashTable,
+    /// class table
+    pub class_table: *mut HashTable,
+    /// constants table
+    pub zend_constants: *mut HashTable,
+    pub vm_stack_top: *mut zval,
+    pub vm_stack_end: *mut zval,
+    pub vm_stack: *mut c_void,
+    #[cfg(feature = "php74")]
+    pub vm_stack_page_size: usize,
+    pub current_execute_data: *mut zend_execute_data,
+    pub fake_scope: *mut zend_class_entry,
+    _rest: [u8; 0],
+}
+
+#[cfg(not(php_zts))]
+extern "C" {
+    pub static mut executor_globals: zend_executor_globals;
+}
+
+#[cfg(all(php_zts, not(feature = "php74")))]
+extern "C" {
+    pub static executor_globals_id: ts_rsrc_id;
+}
+
+#[cfg(all(php_zts, feature = "php74"))]
+extern "C" {
+    pub static executor_globals_offset: usize;
+}
+
+/// Executor globals of the current thread, EG() in C
+#[cfg(not(php_zts))]
+#[inline]
+pub fn executor_globals_ptr() -> *mut zend_executor_globals {
+    // taking the address of a static mut is only safe since Rust 1.82
+    #[allow(unused_unsafe)]
+    unsafe { ptr::addr_of_mut!(executor_globals) }
+}
+
+/// Executor globals of the current thread, EG() in C
+#[cfg(all(php_zts, not(feature = "php74")))]
+#[inline]
+pub fn executor_globals_ptr() -> *mut zend_executor_globals {
+    unsafe { TSRMG_BULK(executor_globals_id) as *mut _ }
+}
+
+/// Executor globals of the current thread, EG() in C. Since PHP 7.4 they are at a fixed
+/// offset from the thread's TSRM cache.
+#[cfg(all(php_zts, feature = "php74"))]
+#[inline]
+pub fn executor_globals_ptr() -> *mut zend_executor_globals {
+    unsafe { (tsrm_get_ls_cache() as *mut u8).add(executor_globals_offset) as *mut _ }
+}
+
+
+/// EG(current_execute_data): frame of the function being executed, null outside of a call
+#[inline]
+pub fn current_execute_data() -> *mut zend_execute_data {
+    unsafe { (*executor_globals_ptr()).current_execute_data }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem;
+
+    #[test]
+    fn zend_executor_globals_offsets() {
+        let offset = if cfg!(feature = "php74") { 488 } else { 480 };
+        let eg = mem::MaybeUninit::<zend_executor_globals>::uninit();
+        let base = eg.as_ptr();
+        let current = unsafe { ptr::addr_of!((*base).current_execute_data) as usize - base as usize };
+        assert_eq!(current, offset, "Offset of: zend_executor_globals::current_execute_data");
+    }
+}
@@ -1,27 +1,56 @@
-use std::{slice, ptr};
+use std::{slice, ptr, mem};
 use std::os::raw::{c_char, c_int, c_void};
 use crate::zend::{
-    HashTable, HashPosition, Bucket, dtor_func_t,zend_bool, zend_ulong, zend_string, zval, zend_type,
-    ZEND_RESULT_CODE, VaList,
+    HashTable, HashPosition, Bucket, dtor_func_t,zend_bool, zend_long, zend_ulong, zend_string, zval, zend_type,
+    ZEND_RESULT_CODE, VaList, IS_ARRAY_IMMUTABLE, GC_FLAGS_SHIFT,
 };
+#[cfg(php_hash_file_line)]
+use crate::zend::zend_file_line;
 
 pub const HASH_KEY_IS_STRING: c_int = 1;
 pub const HASH_KEY_IS_LONG: c_int = 2;
 pub const HASH_KEY_NON_EXISTENT: c_int = 3;
 
+#[cfg(not(feature = "php74"))]
+pub const HASH_FLAG_PERSISTENT: u32 = 1 << 0;
+#[cfg(not(feature = "php74"))]
+pub const HASH_FLAG_APPLY_PROTECTION: u32 = 1 << 1;
+#[cfg(feature = "php74")]
+pub const HASH_FLAG_CONSISTENCY_MASK: u32 = (1 << 0) | (1 << 1);
+pub const HASH_FLAG_PACKED: u32 = 1 << 2;
+#[cfg(not(feature = "php74"))]
+pub const HASH_FLAG_INITIALIZED: u32 = 1 << 3;
+#[cfg(feature = "php74")]
+pub const HASH_FLAG_UNINITIALIZED: u32 = 1 << 3;
+pub const HASH_FLAG_STATIC_KEYS: u32 = 1 << 4;
+pub const HASH_FLAG_HAS_EMPTY_IND: u32 = 1 << 5;
+#[cfg(feature = "php74")]
+pub const HASH_FLAG_ALLOW_COW_VIOLATION: u32 = 1 << 6;
+
+// Debug builds of PHP < 7.3 take ZEND_FILE_LINE_DC arguments in hash initialization and insertions.
+// PHP 7.3 dropped them and exports the insertion functions without the leading underscore.
 extern {
     /** startup/shutdown */
-    fn _zend_hash_init(ht: *mut HashTable, nSize: u32, pDestructor: dtor_func_t, persistent: zend_bool);
-    fn _zend_hash_init_ex(ht: *mut HashTable, nSize: u32, pDestructor: dtor_func_t, persistent: zend_bool, bApplyProtection: zend_bool);
+    fn _zend_hash_init(ht: *mut HashTable, nSize: u32, pDestructor: dtor_func_t, persistent: zend_bool, #[cfg(php_hash_file_line)] __zend_filename: *const c_char, #[cfg(php_hash_file_line)] __zend_lineno: u32);
+    #[cfg(not(feature = "php74"))]
+    fn _zend_hash_init_ex(ht: *mut HashTable, nSize: u32, pDestructor: dtor_func_t, persistent: zend_bool, bApplyProtection: zend_bool, #[cfg(php_hash_file_line)] __zend_filename: *const c_char, #[cfg(php_hash_file_line)] __zend_lineno: u32);
     fn zend_hash_destroy(ht: *mut HashTable);
     fn zend_hash_clean(ht: *mut HashTable);
+    fn zend_hash_real_init(ht: *mut HashTable, packed: zend_bool);
+    fn zend_array_dup(source: *mut HashTable) -> *mut HashTable;
+    fn zend_array_destroy(ht: *mut HashTable);
 
     /** additions/updates/changes */
-    fn _zend_hash_add_or_update(ht: *mut HashTable, key: *mut zend_string, pData: *mut zval, flag: u32) -> *mut zval;
-    fn _zend_hash_update(ht: *mut HashTable, key: *mut zend_string, pData: *mut zval) -> *mut zval;
-    fn _zend_hash_update_ind(ht: *mut HashTable, key: *mut zend_string, pData: *mut zval) -> *mut zval;
-    fn _zend_hash_add(ht: *mut HashTable, key: *mut zend_string, pData: *mut zval) -> *mut zval;
-    fn _zend_hash_add_new(ht: *mut HashTable, key: *mut zend_string, pData: *mut zval) -> *mut zval;
+    #[cfg_attr(feature = "php74", link_name = "zend_hash_add_or_update")]
+    fn _zend_hash_add_or_update(ht: *mut HashTable, key: *mut zend_string, pData: *mut zval, flag: u32, #[cfg(php_hash_file_line)] __zend_filename: *const c_char, #[cfg(php_hash_file_line)] __zend_lineno: u32) -> *mut zval;
+    #[cfg_attr(feature = "php74", link_name = "zend_hash_update")]
+    fn _zend_hash_update(ht: *mut HashTable, key: *mut zend_string, pData: *mut zval, #[cfg(php_hash_file_line)] __zend_filename: *const c_char, #[cfg(php_hash_file_line)] __zend_lineno: u32) -> *mut zval;
+    #[cfg_attr(feature = "php74", link_name = "zend_hash_update_ind")]
+    fn _zend_hash_update_ind(ht: *mut HashTable, key: *mut zend_string, pData: *mut zval, #[cfg(php_hash_file_line)] __zend_filename: *const c_char, #[cfg(php_hash_file_line)] __zend_lineno: u32) -> *mut zval;
+    #[cfg_attr(feature = "php74", link_name = "zend_hash_add")]
+    fn _zend_hash_add(ht: *mut HashTable, key: *mut zend_string, pData: *mut zval, #[cfg(php_hash_file_line)] __zend_filename: *const c_char, #[cfg(php_hash_file_line)] __zend_lineno: u32) -> *mut zval;
+    #[cfg_attr(feature = "php74", link_name = "zend_hash_add_new")]
+    fn _zend_hash_add_new(ht: *mut HashTable, key: *mut zend_string, pData: *mut zval, #[cfg(php_hash_file_line)] __zend_filename: *const c_char, #[cfg(php_hash_file_line)] __zend_lineno: u32) -> *mut zval;
 
 //#define zend_hash_update(ht, key, pData) \
 //_zend_hash_update(ht, key, pData ZEND_FILE_LINE_CC)
@@ -32,11 +61,16 @@ extern {
 //#define zend_hash_add_new(ht, key, pData) \
 //_zend_hash_add_new(ht, key, pData ZEND_FILE_LINE_CC)
 
-    fn _zend_hash_str_add_or_update(ht: *mut HashTable, key: *const c_char, len: usize, pData: *mut zval, flag: u32) -> *mut zval;
-    fn _zend_hash_str_update(ht: *mut HashTable, key: *const c_char, len: usize, pData: *mut zval) -> *mut zval;
-    fn _zend_hash_str_update_ind(ht: *mut HashTable, key: *const c_char, len: usize, pData: *mut zval) -> *mut zval;
-    fn _zend_hash_str_add(ht: *mut HashTable, key: *const c_char, len: usize, pData: *mut zval) -> *mut zval;
-    fn _zend_hash_str_add_new(ht: *mut HashTable, key: *const c_char, len: usize, pData: *mut zval) -> *mut zval;
+    #[cfg_attr(feature = "php74", link_name = "zend_hash_str_add_or_update")]
+    fn _zend_hash_str_add_or_update(ht: *mut HashTable, key: *const c_char, len: usize, pData: *mut zval, flag: u32, #[cfg(php_hash_file_line)] __zend_filename: *const c_char, #[cfg(php_hash_file_line)] __zend_lineno: u32) -> *mut zval;
+    #[cfg_attr(feature = "php74", link_name = "zend_hash_str_update")]
+    fn _zend_hash_str_update(ht: *mut HashTable, key: *const c_char, len: usize, pData: *mut zval, #[cfg(php_hash_file_line)] __zend_filename: *const c_char, #[cfg(php_hash_file_line)] __zend_lineno: u32) -> *mut zval;
+    #[cfg_attr(feature = "php74", link_name = "zend_hash_str_update_ind")]
+    fn _zend_hash_str_update_ind(ht: *mut HashTable, key: *const c_char, len: usize, pData: *mut zval, #[cfg(php_hash_file_line)] __zend_filename: *const c_char, #[cfg(php_hash_file_line)] __zend_lineno: u32) -> *mut zval;
+    #[cfg_attr(feature = "php74", link_name = "zend_hash_str_add")]
+    fn _zend_hash_str_add(ht: *mut HashTable, key: *const c_char, len: usize, pData: *mut zval, #[cfg(php_hash_file_line)] __zend_filename: *const c_char, #[cfg(php_hash_file_line)] __zend_lineno: u32) -> *mut zval;
+    #[cfg_attr(feature = "php74", link_name = "zend_hash_str_add_new")]
+    fn _zend_hash_str_add_new(ht: *mut HashTable, key: *const c_char, len: usize, pData: *mut zval, #[cfg(php_hash_file_line)] __zend_filename: *const c_char, #[cfg(php_hash_file_line)] __zend_lineno: u32) -> *mut zval;
 
 //#define zend_hash_str_update(ht, key, len, pData) \
 //_zend_hash_str_update(ht, key, len, pData ZEND_FILE_LINE_CC)
@@ -47,12 +81,18 @@ extern {
 //#define zend_hash_str_add_new(ht, key, len, pData) \
 //_zend_hash_str_add_new(ht, key, len, pData ZEND_FILE_LINE_CC)
 
-    fn _zend_hash_index_add_or_update(ht: *mut HashTable, h: zend_ulong, pData: *mut zval, flag: u32) -> *mut zval;
-    fn _zend_hash_index_add(ht: *mut HashTable, h: zend_ulong, pData: *mut zval) -> *mut zval;
-    fn _zend_hash_index_add_new(ht: *mut HashTable, h: zend_ulong, pData: *mut zval) -> *mut zval;
-    fn _zend_hash_index_update(ht: *mut HashTable, h: zend_ulong, pData: *mut zval) -> *mut zval;
-    fn _zend_hash_next_index_insert(ht: *mut HashTable, pData: *mut zval) -> *mut zval;
-    fn _zend_hash_next_index_insert_new(ht: *mut HashTable, pData: *mut zval) -> *mut zval;
+    #[cfg_attr(feature = "php74", link_name = "zend_hash_index_add_or_update")]
+    fn _zend_hash_index_add_or_update(ht: *mut HashTable, h: zend_ulong, pData: *mut zval, flag: u32, #[cfg(php_hash_file_line)] __zend_filename: *const c_char, #[cfg(php_hash_file_line)] __zend_lineno: u32) -> *mut zval;
+    #[cfg_attr(feature = "php74", link_name = "zend_hash_index_add")]
+    fn _zend_hash_index_add(ht: *mut HashTable, h: zend_ulong, pData: *mut zval, #[cfg(php_hash_file_line)] __zend_filename: *const c_char, #[cfg(php_hash_file_line)] __zend_lineno: u32) -> *mut zval;
+    #[cfg_attr(feature = "php74", link_name = "zend_hash_index_add_new")]
+    fn _zend_hash_index_add_new(ht: *mut HashTable, h: zend_ulong, pData: *mut zval, #[cfg(php_hash_file_line)] __zend_filename: *const c_char, #[cfg(php_hash_file_line)] __zend_lineno: u32) -> *mut zval;
+    #[cfg_attr(feature = "php74", link_name = "zend_hash_index_update")]
+    fn _zend_hash_index_update(ht: *mut HashTable, h: zend_ulong, pData: *mut zval, #[cfg(php_hash_file_line)] __zend_filename: *const c_char, #[cfg(php_hash_file_line)] __zend_lineno: u32) -> *mut zval;
+    #[cfg_attr(feature = "php74", link_name = "zend_hash_next_index_insert")]
+    fn _zend_hash_next_index_insert(ht: *mut HashTable, pData: *mut zval, #[cfg(php_hash_file_line)] __zend_filename: *const c_char, #[cfg(php_hash_file_line)] __zend_lineno: u32) -> *mut zval;
+    #[cfg_attr(feature = "php74", link_name = "zend_hash_next_index_insert_new")]
+    fn _zend_hash_next_index_insert_new(ht: *mut HashTable, pData: *mut zval, #[cfg(php_hash_file_line)] __zend_filename: *const c_char, #[cfg(php_hash_file_line)] __zend_lineno: u32) -> *mut zval;
 
 //#define zend_hash_index_add(ht, h, pData) \
 //_zend_hash_index_add(ht, h, pData ZEND_FILE_LINE_CC)
@@ -83,6 +123,21 @@ extern {
     fn zend_hash_index_del(ht: *mut HashTable, h: zend_ulong) -> ZEND_RESULT_CODE;
     fn zend_hash_del_bucket(ht: *mut HashTable, p: *mut Bucket);
 
+    /** Copying, merging and sorting */
+    #[cfg(not(feature = "php74"))]
+    fn _zend_hash_merge(target: *mut HashTable, source: *mut HashTable, pCopyConstructor: copy_ctor_func_t, overwrite: zend_bool, #[cfg(php_hash_file_line)] __zend_filename: *const c_char, #[cfg(php_hash_file_line)] __zend_lineno: u32);
+    #[cfg(feature = "php74")]
+    fn zend_hash_merge(target: *mut HashTable, source: *mut HashTable, pCopyConstructor: copy_ctor_func_t, overwrite: zend_bool);
+    #[cfg(not(feature = "php80"))]
+    fn zend_hash_sort_ex(ht: *mut HashTable, sort_func: sort_func_t, compar: compare_func_t, renumber: zend_bool) -> ZEND_RESULT_CODE;
+    #[cfg(feature = "php80")]
+    fn zend_hash_sort_ex(ht: *mut HashTable, sort_func: sort_func_t, compar: compare_func_t, renumber: zend_bool);
+    fn zend_hash_compare(ht1: *mut HashTable, ht2: *mut HashTable, compar: compare_func_t, ordered: zend_bool) -> c_int;
+    pub fn zend_sort(base: *mut c_void, nmemb: usize, siz: usize, cmp: compare_func_t, swp: swap_func_t);
+
+    /** ext/standard */
+    fn php_array_merge(dest: *mut HashTable, src: *mut HashTable) -> c_int;
+
     /** Data retreival */
     fn zend_hash_find(ht: *const HashTable, key: *mut zend_string) -> *mut zval;
     fn zend_hash_str_find(ht: *const HashTable, key: *const c_char, len: usize) -> *mut zval;
@@ -108,6 +163,12 @@ pub const ZEND_HASH_APPLY_KEEP: zend_type = 0;
 pub const ZEND_HASH_APPLY_REMOVE: zend_type = 1 << 0;
 pub const ZEND_HASH_APPLY_STOP: zend_type = 1 << 1;
 
+pub type copy_ctor_func_t = Option<unsafe extern "C" fn(pElement: *mut zval)>;
+/// Compares buckets in sort functions and values in zend_hash_compare
+pub type compare_func_t = extern "C" fn(a: *const c_void, b: *const c_void) -> c_int;
+pub type swap_func_t = extern "C" fn(a: *mut c_void, b: *mut c_void);
+pub type sort_func_t = unsafe extern "C" fn(base: *mut c_void, nmemb: usize, siz: usize, cmp: compare_func_t, swp: swap_func_t);
+
 pub type apply_func_t = extern fn(pDest: *mut zval) -> c_int;
 pub type apply_func_arg_t = extern fn(pDest: *mut zval, argument: *mut c_void) -> c_int;
 pub type apply_func_args_t = extern fn(pDest: *mut zval, num_args: c_int, args: VaList, hash_key: *mut zend_hash_key) -> c_int;
@@ -119,14 +180,27 @@ pub struct zend_hash_key {
 }
 
 impl HashTable {
+    #[track_caller]
     #[inline]
     pub fn init(ht: *mut HashTable, n_size: u32, _hash_function: (), destructor: dtor_func_t, persistent: bool) {
-        unsafe { _zend_hash_init(ht, n_size, destructor, if persistent { 1 } else { 0 }) };
+        #[cfg(php_hash_file_line)]
+        let (file, line) = zend_file_line();
+        unsafe { _zend_hash_init(ht, n_size, destructor, if persistent { 1 } else { 0 }, #[cfg(php_hash_file_line)] file, #[cfg(php_hash_file_line)] line) };
     }
 
+    #[track_caller]
     #[inline]
     pub fn init_ex(ht: *mut HashTable, n_size: u32, _hash_function: (), destructor: dtor_func_t, persistent: bool, apply_protection: bool) {
-        unsafe { _zend_hash_init_ex(ht, n_size, destructor, if persistent { 1 } else { 0 }, if apply_protection { 1 } else { 0 }) };
+        #[cfg(php_hash_file_line)]
+        let (file, line) = zend_file_line();
+        #[cfg(not(feature = "php74"))]
+        unsafe { _zend_hash_init_ex(ht, n_size, destructor, if persistent { 1 } else { 0 }, if apply_protection { 1 } else { 0 }, #[cfg(php_hash_file_line)] file, #[cfg(php_hash_file_line)] line) };
+        // apply protection is always on since PHP 7.3
+        #[cfg(feature = "php74")]
+        {
+            let _ = apply_protection;
+            unsafe { _zend_hash_init(ht, n_size, destructor, if persistent { 1 } else { 0 }) };
+        }
     }
 
     #[inline]
@@ -139,6 +213,36 @@ impl HashTable {
         unsafe { zend_hash_clean(ht) };
     }
 
+    /// Allocates the buckets of an initialized array, packed arrays are indexed by position
+    ///
+    /// # Safety
+    /// `ht` must point to an array initialized with `init_ex` and not allocated yet
+    #[inline]
+    pub unsafe fn real_init(ht: *mut HashTable, packed: bool) {
+        zend_hash_real_init(ht, packed as zend_bool)
+    }
+
+    /// HT_FLAGS
+    #[inline]
+    pub fn flags(&self) -> u32 {
+        unsafe { self.u.flags }
+    }
+
+    /// HT_IS_PACKED: integer keys only, stored by position without a hash index.
+    /// Since PHP 8.2 the slots of packed arrays are zvals instead of buckets.
+    #[inline]
+    pub fn is_packed(&self) -> bool {
+        self.flags() & HASH_FLAG_PACKED != 0
+    }
+
+    /// HASH_FLAG_HAS_EMPTY_IND: some IS_INDIRECT elements point to UNDEF and are still counted
+    #[inline]
+    pub fn has_empty_ind(&self) -> bool {
+        self.flags() & HASH_FLAG_HAS_EMPTY_IND != 0
+    }
+
+    /// # Safety
+    /// `ht` must point to a valid initialized array and `pos` to a position in it
     #[inline]
     pub unsafe fn has_more_elements_ex(ht: *mut HashTable, pos: *mut HashPosition) -> ZEND_RESULT_CODE {
         if zend_hash_get_current_key_type_ex(ht, pos) == HASH_KEY_NON_EXISTENT {
@@ -148,6 +252,8 @@ impl HashTable {
         }
     }
 
+    /// # Safety
+    /// `ht` must point to a valid initialized array and `key` to a valid string
     #[inline]
     pub unsafe fn find_ind(ht: *const HashTable, key: *mut zend_string) -> *mut zval {
         let zv = zend_hash_find(ht, key);
@@ -164,6 +270,8 @@ impl HashTable {
         }
     }
 
+    /// # Safety
+    /// `ht` must point to a valid initialized array and `key` to a valid string
     #[inline]
     pub unsafe fn exists_ind(ht: *const HashTable, key: *mut zend_string) -> bool {
         let zv = zend_hash_find(ht, key);
@@ -171,6 +279,8 @@ impl HashTable {
         !zv.is_null() && (!(*zv).is_indirect() || !(*(*zv).indirect()).is_undef())
     }
 
+    /// # Safety
+    /// `ht` must point to a valid initialized array and `key` to `len` readable bytes
     #[inline]
     pub unsafe fn str_find_ind(ht: *const HashTable, key: *const c_char, len: usize) -> *mut zval {
         let zv = zend_hash_str_find(ht, key, len);
@@ -187,6 +297,8 @@ impl HashTable {
         }
     }
 
+    /// # Safety
+    /// `ht` must point to a valid initialized array and `key` to `len` readable bytes
     #[inline]
     pub unsafe fn str_exists_ind(ht: *const HashTable, key: *const c_char, len: usize) -> bool {
         let zv = zend_hash_str_find(ht, key, len);
@@ -194,14 +306,239 @@ impl HashTable {
         !zv.is_null() && (!(*zv).is_indirect() || !(*(*zv).indirect()).is_undef())
     }
 
+    /// # Safety
+    /// `ht` must point to a valid initialized array, not shared with other references, `key` to a valid string. The array takes the reference held by `val`.
+    #[track_caller]
     #[inline]
     pub unsafe fn update_ind(ht: *mut HashTable, key: *mut zend_string, val: *mut zval) -> *mut zval {
-        _zend_hash_update_ind(ht, key, val)
+        #[cfg(php_hash_file_line)]
+        let (file, line) = zend_file_line();
+        _zend_hash_update_ind(ht, key, val, #[cfg(php_hash_file_line)] file, #[cfg(php_hash_file_line)] line)
     }
 
+    /// # Safety
+    /// `ht` must point to a valid initialized array, not shared with other references, `key` to `len` readable bytes. The array takes the reference held by `val`.
+    #[track_caller]
     #[inline]
     pub unsafe fn str_update_ind(ht: *mut HashTable, key: *const c_char, len: usize, val: *mut zval) -> *mut zval {
-        _zend_hash_str_update_ind(ht, key, len, val)
+        #[cfg(php_hash_file_line)]
+        let (file, line) = zend_file_line();
+        _zend_hash_str_update_ind(ht, key, len, val, #[cfg(php_hash_file_line)] file, #[cfg(php_hash_file_line)] line)
+    }
+
+    /// # Safety
+    /// `ht` must point to a valid initialized array, not shared with other references. The array takes the reference held by `val`.
+    #[track_caller]
+    #[inline]
+    pub unsafe fn next_index_insert(ht: *mut HashTable, val: *mut zval) -> *mut zval {
+        #[cfg(php_hash_file_line)]
+        let (file, line) = zend_file_line();
+        _zend_hash_next_index_insert(ht, val, #[cfg(php_hash_file_line)] file, #[cfg(php_hash_file_line)] line)
+    }
+
+    /// # Safety
+    /// `ht` must point to a valid initialized array, not shared with other references, `key` to a valid string. The array takes the reference held by `val`.
+    #[track_caller]
+    #[inline]
+    pub unsafe fn add(ht: *mut HashTable, key: *mut zend_string, val: *mut zval) -> *mut zval {
+        #[cfg(php_hash_file_line)]
+        let (file, line) = zend_file_line();
+        _zend_hash_add(ht, key, val, #[cfg(php_hash_file_line)] file, #[cfg(php_hash_file_line)] line)
+    }
+
+    /// # Safety
+    /// `ht` must point to a valid initialized array, not shared with other references, `key` to `len` readable bytes. The array takes the reference held by `val`.
+    #[track_caller]
+    #[inline]
+    pub unsafe fn str_add(ht: *mut HashTable, key: *const c_char, len: usize, val: *mut zval) -> *mut zval {
+        #[cfg(php_hash_file_line)]
+        let (file, line) = zend_file_line();
+        _zend_hash_str_add(ht, key, len, val, #[cfg(php_hash_file_line)] file, #[cfg(php_hash_file_line)] line)
+    }
+
+    /// # Safety
+    /// `ht` must point to a valid initialized array
+    #[inline]
+    pub unsafe fn index_find(ht: *const HashTable, h: zend_ulong) -> *mut zval {
+        zend_hash_index_find(ht, h)
+    }
+
+    /// # Safety
+    /// `ht` must point to a valid initialized array
+    #[inline]
+    pub unsafe fn index_exists(ht: *const HashTable, h: zend_ulong) -> bool {
+        zend_hash_index_exists(ht, h) != 0
+    }
+
+    /// # Safety
+    /// `ht` must point to a valid initialized array, not shared with other references. The array takes the reference held by `val`.
+    #[track_caller]
+    #[inline]
+    pub unsafe fn index_update(ht: *mut HashTable, h: zend_ulong, val: *mut zval) -> *mut zval {
+        #[cfg(php_hash_file_line)]
+        let (file, line) = zend_file_line();
+        _zend_hash_index_update(ht, h, val, #[cfg(php_hash_file_line)] file, #[cfg(php_hash_file_line)] line)
+    }
+
+    /// # Safety
+    /// `ht` must point to a valid initialized array, not shared with other references. The array takes the reference held by `val`.
+    #[track_caller]
+    #[inline]
+    pub unsafe fn index_add(ht: *mut HashTable, h: zend_ulong, val: *mut zval) -> *mut zval {
+        #[cfg(php_hash_file_line)]
+        let (file, line) = zend_file_line();
+        _zend_hash_index_add(ht, h, val, #[cfg(php_hash_file_line)] file, #[cfg(php_hash_file_line)] line)
+    }
+
+    /// # Safety
+    /// `ht` must point to a valid initialized array, not shared with other references and `key` to a valid string
+    #[inline]
+    pub unsafe fn del_ind(ht: *mut HashTable, key: *mut zend_string) -> bool {
+        zend_hash_del_ind(ht, key).is_success()
+    }
+
+    /// # Safety
+    /// `ht` must point to a valid initialized array, not shared with other references and `key` to `len` readable bytes
+    #[inline]
+    pub unsafe fn str_del_ind(ht: *mut HashTable, key: *const c_char, len: usize) -> bool {
+        zend_hash_str_del_ind(ht, key, len).is_success()
+    }
+
+    /// # Safety
+    /// `ht` must point to a valid initialized array, not shared with other references
+    #[inline]
+    pub unsafe fn index_del(ht: *mut HashTable, h: zend_ulong) -> bool {
+        zend_hash_index_del(ht, h).is_success()
+    }
+
+    /// Removes the bucket in place, other buckets keep their positions
+    ///
+    /// # Safety
+    /// `ht` must point to a valid initialized array, not shared with other references, `p` to one of its used buckets
+    #[inline]
+    pub unsafe fn del_bucket(ht: *mut HashTable, p: *mut Bucket) {
+        zend_hash_del_bucket(ht, p)
+    }
+
+    /// Like `del_bucket`, but an IS_INDIRECT bucket is kept and its target is released and
+    /// made UNDEF, as zend_hash_del_ind does for symbol and property tables
+    ///
+    /// # Safety
+    /// `ht` must point to a valid initialized array, not shared with other references, `p` to one of its used buckets
+    #[inline]
+    pub unsafe fn del_bucket_ind(ht: *mut HashTable, p: *mut Bucket) {
+        if !(*p).val.is_indirect() {
+            return zend_hash_del_bucket(ht, p);
+        }
+        let data = (*p).val.indirect();
+        if (*data).is_undef() {
+            return;
+        }
+        let mut tmp = *data;
+        (*data).set_undef();
+        if let Some(destructor) = (*ht).pDestructor {
+            destructor(&mut tmp);
+        }
+        (*ht).u.flags |= HASH_FLAG_HAS_EMPTY_IND;
+    }
+
+    /// # Safety
+    /// `ht` must point to a valid initialized array, not shared with other references, `apply_func` must accept `argument`
+    #[inline]
+    pub unsafe fn apply_with_argument(ht: *mut HashTable, apply_func: apply_func_arg_t, argument: *mut c_void) {
+        zend_hash_apply_with_argument(ht, apply_func, argument)
+    }
+
+    /// zend_hash_merge: copies the elements of `source`, existing keys are kept unless `overwrite`
+    ///
+    /// # Safety
+    /// `target` and `source` must point to valid initialized arrays, `target` not shared with other references
+    #[track_caller]
+    #[inline]
+    pub unsafe fn merge(target: *mut HashTable, source: *mut HashTable, copy_ctor: copy_ctor_func_t, overwrite: bool) {
+        #[cfg(not(feature = "php74"))]
+        {
+            #[cfg(php_hash_file_line)]
+            let (file, line) = zend_file_line();
+            _zend_hash_merge(target, source, copy_ctor, overwrite as zend_bool, #[cfg(php_hash_file_line)] file, #[cfg(php_hash_file_line)] line)
+        }
+        #[cfg(feature = "php74")]
+        zend_hash_merge(target, source, copy_ctor, overwrite as zend_bool)
+    }
+
+    /// php_array_merge: string keys are overwritten, integer keys are appended
+    ///
+    /// # Safety
+    /// `dest` and `src` must point to valid initialized arrays, `dest` not shared with other references
+    #[inline]
+    pub unsafe fn array_merge(dest: *mut HashTable, src: *mut HashTable) {
+        php_array_merge(dest, src);
+    }
+
+    /// zend_hash_sort_ex: `compar` receives buckets, holes are removed before sorting.
+    /// Keys are renumbered from 0 if `renumber`.
+    ///
+    /// # Safety
+    /// `ht` must point to a valid initialized array, not shared with other references, `compar` must compare buckets
+    #[inline]
+    pub unsafe fn sort_ex(ht: *mut HashTable, sort_func: sort_func_t, compar: compare_func_t, renumber: bool) {
+        zend_hash_sort_ex(ht, sort_func, compar, renumber as zend_bool);
+    }
+
+    /// zend_hash_compare: element count difference, or the first non-equal comparison of values
+    /// with the same key. If `ordered`, keys must also be in the same order.
+    ///
+    /// # Safety
+    /// `ht1` and `ht2` must point to valid initialized arrays, `compar` must compare zvals
+    #[inline]
+    pub unsafe fn compare(ht1: *mut HashTable, ht2: *mut HashTable, compar: compare_func_t, ordered: bool) -> c_int {
+        zend_hash_compare(ht1, ht2, compar, ordered as zend_bool)
+    }
+
+    /// zend_array_dup: copy with refcount 1, values are shared with the source
+    ///
+    /// # Safety
+    /// `ht` must point to a valid initialized array
+    #[inline]
+    pub unsafe fn array_dup(ht: *mut HashTable) -> *mut HashTable {
+        zend_array_dup(ht)
+    }
+
+    /// zend_array_destroy: destroys the values and frees the array
+    ///
+    /// # Safety
+    /// `ht` must point to a valid array without other references, it is freed
+    #[inline]
+    pub unsafe fn array_destroy(ht: *mut HashTable) {
+        zend_array_destroy(ht)
+    }
+
+    /// Arrays in opcache shared memory, they are neither refcounted nor modified
+    #[inline]
+    pub fn is_immutable(&self) -> bool {
+        unsafe { self.gc.u.type_info & (IS_ARRAY_IMMUTABLE << GC_FLAGS_SHIFT) != 0 }
+    }
+
+    /// GC_ADDREF, skipped for immutable arrays
+    #[inline]
+    pub fn addref(&mut self) {
+        if !self.is_immutable() {
+            self.gc.refcount += 1;
+        }
+    }
+
+    /// zend_array_release: destroys the array when the last reference is released
+    ///
+    /// # Safety
+    /// `ht` must point to a valid array owning one reference, it may be freed
+    #[inline]
+    pub unsafe fn release(ht: *mut HashTable) {
+        if !(*ht).is_immutable() {
+            (*ht).gc.refcount -= 1;
+            if (*ht).gc.refcount == 0 {
+                zend_array_destroy(ht);
+            }
+        }
     }
 /*
 
@@ -657,13 +994,26 @@ static zend_always_inline void *zend_hash_get_current_data_ptr_ex(ht: *mut HashT
 */
 
 
+    /// Used slots of a hash array, holes included
+    ///
+    /// # Safety
+    /// The array must be initialized and outlive the returned slice. Packed arrays store zvals
+    /// since PHP 8.2, this asserts they are not passed.
     #[inline]
     pub unsafe fn buckets(&self) -> &[Bucket] {
+        #[cfg(feature = "php82")]
+        assert!(!self.is_packed(), "Packed arrays have no buckets since PHP 8.2");
         slice::from_raw_parts(self.arData, self.nNumUsed as usize)
     }
 
+    /// Mutable `buckets`, with the same restriction on packed arrays
+    ///
+    /// # Safety
+    /// The array must be initialized and outlive the returned slice, without other references to its buckets
     #[inline]
     pub unsafe fn buckets_mut(&mut self) -> &mut [Bucket] {
+        #[cfg(feature = "php82")]
+        assert!(!self.is_packed(), "Packed arrays have no buckets since PHP 8.2");
         slice::from_raw_parts_mut(self.arData, self.nNumUsed as usize)
     }
 
@@ -674,4 +1024,59 @@ static zend_always_inline void *zend_hash_get_current_data_ptr_ex(ht: *mut HashT
 ////            f(b, z)
 //        }
 //    }
-}
\ No newline at end of file
+}
+
+const MAX_LENGTH_OF_LONG: usize = if mem::size_of::<zend_long>() == 8 { 20 } else { 11 };
+
+/// ZEND_HANDLE_NUMERIC_STR: integer key for strings in canonical decimal form, like "42" or "-7",
+/// which symbol tables (PHP arrays) store as integer keys. "042", "+1", "1.0" and overflowing
+/// numbers stay string keys.
+pub fn handle_numeric_str(key: &[u8]) -> Option<zend_ulong> {
+    let digits = match key {
+        [b'-', rest @ ..] => rest,
+        _ => key,
+    };
+    match digits {
+        [] => return None,
+        [b'0', ..] if key.len() > 1 => return None,
+        _ if digits.len() > MAX_LENGTH_OF_LONG - 1 => return None,
+        _ if mem::size_of::<zend_long>() == 4 && digits.len() == MAX_LENGTH_OF_LONG - 1 && digits[0] > b'2' => return None,
+        _ => {}
+    }
+    let mut idx: zend_ulong = 0;
+    for &c in digits {
+        if !c.is_ascii_digit() {
+            return None;
+        }
+        idx = idx.wrapping_mul(10).wrapping_add((c - b'0') as zend_ulong);
+    }
+    if digits.len() < key.len() {
+        if idx.wrapping_sub(1) > zend_long::MAX as zend_ulong {
+            return None;
+        }
+        Some(idx.wrapping_neg())
+    } else if idx > zend_long::MAX as zend_ulong {
+        None
+    } else {
+        Some(idx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numeric_string_keys() {
+        assert_eq!(handle_numeric_str(b"0"), Some(0));
+        assert_eq!(handle_numeric_str(b"42"), Some(42));
+        assert_eq!(handle_numeric_str(b"-7"), Some(-7 as zend_long as zend_ulong));
+        assert_eq!(handle_numeric_str(b"9223372036854775807"), Some(zend_long::MAX as zend_ulong));
+        assert_eq!(handle_numeric_str(b"-9223372036854775808"), Some(zend_long::MIN as zend_ulong));
+
+        for key in [&b""[..], b"-", b"-0", b"042", b"+1", b" 1", b"1 ", b"1.0", b"1e3", b"abc",
+                    b"9223372036854775808", b"-9223372036854775809", b"100000000000000000000"] {
+            assert_eq!(handle_numeric_str(key), None, "{:?}", String::from_utf8_lossy(key));
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use std::os::raw::{c_char, c_int};
+use crate::zend::Module;
+
+extern "C" {
+    pub fn php_info_print_table_start();
+    pub fn php_info_print_table_end();
+    pub fn php_info_print_table_header(num_cols: c_int, ...);
+    pub fn php_info_print_table_row(num_cols: c_int, ...);
+    pub fn php_info_print_table_colspan_header(num_cols: c_int, header: *mut c_char);
+    pub fn php_info_print_box_start(bg: c_int);
+    pub fn php_info_print_box_end();
+    pub fn php_info_print_hr();
+
+    /// DISPLAY_INI_ENTRIES()
+    pub fn display_ini_entries(module: *mut Module);
+}
@@ -0,0 +1,96 @@
+use std::os::raw::{c_char, c_int, c_uint, c_void};
+use crate::zend::{zend_string, zend_long, zend_bool};
+
+pub const ZEND_INI_USER: c_int = 1 << 0;
+pub const ZEND_INI_PERDIR: c_int = 1 << 1;
+pub const ZEND_INI_SYSTEM: c_int = 1 << 2;
+pub const ZEND_INI_ALL: c_int = ZEND_INI_USER | ZEND_INI_PERDIR | ZEND_INI_SYSTEM;
+
+pub const PHP_INI_USER: c_int = ZEND_INI_USER;
+pub const PHP_INI_PERDIR: c_int = ZEND_INI_PERDIR;
+pub const PHP_INI_SYSTEM: c_int = ZEND_INI_SYSTEM;
+pub const PHP_INI_ALL: c_int = ZEND_INI_ALL;
+
+pub const ZEND_INI_DISPLAY_ORIG: c_int = 1;
+pub const ZEND_INI_DISPLAY_ACTIVE: c_int = 2;
+
+pub const ZEND_INI_STAGE_STARTUP: c_int = 1 << 0;
+pub const ZEND_INI_STAGE_SHUTDOWN: c_int = 1 << 1;
+pub const ZEND_INI_STAGE_ACTIVATE: c_int = 1 << 2;
+pub const ZEND_INI_STAGE_DEACTIVATE: c_int = 1 << 3;
+pub const ZEND_INI_STAGE_RUNTIME: c_int = 1 << 4;
+pub const ZEND_INI_STAGE_HTACCESS: c_int = 1 << 5;
+
+/// ZEND_INI_MH
+pub type zend_ini_mh = Option<unsafe extern "C" fn(
+    entry: *mut zend_ini_entry,
+    new_value: *mut zend_string,
+    mh_arg1: *mut c_void,
+    mh_arg2: *mut c_void,
+    mh_arg3: *mut c_void,
+    stage: c_int,
+) -> c_int>;
+
+/// ZEND_INI_DISP
+pub type zend_ini_disp = Option<unsafe extern "C" fn(ini_entry: *mut zend_ini_entry, type_: c_int)>;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct zend_ini_entry_def {
+    pub name: *const c_char,
+    pub on_modify: zend_ini_mh,
+    pub mh_arg1: *mut c_void,
+    pub mh_arg2: *mut c_void,
+    pub mh_arg3: *mut c_void,
+    pub value: *const c_char,
+    pub displayer: zend_ini_disp,
+    pub modifiable: c_int,
+    pub name_length: c_uint,
+    pub value_length: c_uint,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct zend_ini_entry {
+    pub name: *mut zend_string,
+    pub on_modify: zend_ini_mh,
+    pub mh_arg1: *mut c_void,
+    pub mh_arg2: *mut c_void,
+    pub mh_arg3: *mut c_void,
+    pub value: *mut zend_string,
+    pub orig_value: *mut zend_string,
+    pub displayer: zend_ini_disp,
+    pub modifiable: c_int,
+    pub orig_modifiable: c_int,
+    pub modified: c_int,
+    pub module_number: c_int,
+}
+
+extern "C" {
+    pub fn zend_register_ini_entries(ini_entry: *const zend_ini_entry_def, module_number: c_int) -> c_int;
+    pub fn zend_unregister_ini_entries(module_number: c_int);
+
+    pub fn zend_ini_long(name: *mut c_char, name_length: c_uint, orig: c_int) -> zend_long;
+    pub fn zend_ini_double(name: *mut c_char, name_length: c_uint, orig: c_int) -> f64;
+    pub fn zend_ini_string(name: *mut c_char, name_length: c_uint, orig: c_int) -> *mut c_char;
+    pub fn zend_ini_string_ex(name: *mut c_char, name_length: c_uint, orig: c_int, exists: *mut zend_bool) -> *mut c_char;
+
+    pub fn zend_ini_boolean_displayer_cb(ini_entry: *mut zend_ini_entry, type_: c_int);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem;
+
+    #[test]
+    fn zend_ini_entry_layout() {
+        assert_eq!(
+            mem::size_of::<zend_ini_entry_def>(), 72, concat!("Size of: ", stringify!(zend_ini_entry_def))
+        );
+        assert_eq!(
+            mem::size_of::<zend_ini_entry>(), 80, concat!("Size of: ", stringify!(zend_ini_entry))
+        );
+    }
+}
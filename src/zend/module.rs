@@ -1,12 +1,300 @@
 use std;
 use std::mem;
+use std::ffi::{CStr, CString};
+use std::sync::{Arc, Mutex, PoisonError};
 use libc::*;
-use crate::c_str;
+use crate::{
+    c_str, zend, catch_panic, ClassBuilder, PanicContext, ModuleContext, StartupType, LifecycleResult,
+    ExtensionLifecycle, ModuleGlobals, IniEntry, IniError, IniValue, InfoTable,
+};
+use crate::ini::IniEntryDef;
 use crate::function::*;
 
-pub struct ModuleDep {}
+#[cfg(not(php_zts))]
+macro_rules! zend_build_ts {
+    () => { ",NTS" };
+}
+
+#[cfg(php_zts)]
+macro_rules! zend_build_ts {
+    () => { ",TS" };
+}
+
+#[cfg(not(php_debug))]
+macro_rules! zend_build_debug {
+    () => { "" };
+}
+
+#[cfg(php_debug)]
+macro_rules! zend_build_debug {
+    () => { ",debug" };
+}
+
+/// ZEND_MODULE_API_NO and the matching ZEND_MODULE_BUILD_ID, which the engine compares
+/// with its own before loading the extension
+macro_rules! zend_module_api {
+    ($api_no:literal) => {
+        pub const ZEND_MODULE_API_NO: c_uint = $api_no;
+        const ZEND_MODULE_BUILD_ID: &str = concat!("API", stringify!($api_no), zend_build_ts!(), zend_build_debug!(), "\0");
+    };
+}
+
+#[cfg(not(feature = "php74"))]
+zend_module_api!(20151012);
+#[cfg(all(feature = "php74", not(feature = "php80")))]
+zend_module_api!(20190902);
+#[cfg(all(feature = "php80", not(feature = "php81")))]
+zend_module_api!(20200930);
+#[cfg(all(feature = "php81", not(feature = "php82")))]
+zend_module_api!(20210902);
+#[cfg(all(feature = "php82", not(feature = "php83")))]
+zend_module_api!(20220829);
+#[cfg(feature = "php83")]
+zend_module_api!(20230831);
+
+type Hook = Arc<dyn Fn(&ModuleContext) -> LifecycleResult + Send + Sync>;
+
+/// Module state kept on the Rust side: PHP copies the module entry on registration,
+/// so startup callbacks reach it through this static
+struct ModuleHooks {
+    /// Set on module startup, post_deactivate receives no arguments
+    context: Option<ModuleContext>,
+    module_startup: Option<Hook>,
+    module_shutdown: Option<Hook>,
+    request_startup: Option<Hook>,
+    request_shutdown: Option<Hook>,
+    post_deactivate: Option<Hook>,
+    info: Option<Arc<dyn Fn() + Send + Sync>>,
+    /// Render the support, version and build table before the info function output
+    default_info: bool,
+    classes: Vec<ClassBuilder>,
+    ini_entries: Vec<IniEntryDef>,
+    /// Null-terminated array the module entry points to
+    deps: Vec<ModuleDep>,
+    /// INI entries were registered on startup and have to be unregistered on shutdown
+    ini_registered: bool,
+}
+
+static HOOKS: Mutex<ModuleHooks> = Mutex::new(ModuleHooks {
+    context: None,
+    module_startup: None,
+    module_shutdown: None,
+    request_startup: None,
+    request_shutdown: None,
+    post_deactivate: None,
+    info: None,
+    default_info: false,
+    classes: Vec::new(),
+    ini_entries: Vec::new(),
+    deps: Vec::new(),
+    ini_registered: false,
+});
+
+fn hooks() -> std::sync::MutexGuard<'static, ModuleHooks> {
+    HOOKS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn report(level: c_int, message: String) {
+    let message = CString::new(message).unwrap_or_default();
+    unsafe { zend::zend_error(level, c_str!("%s"), message.as_ptr()) };
+}
+
+/// Runs a hook outside of the HOOKS lock, errors are reported with the `level` error
+fn run_hook(hook: Option<Hook>, ctx: &ModuleContext, panic_ctx: PanicContext, level: c_int) -> c_int {
+    let hook = match hook {
+        Some(hook) => hook,
+        None => return zend::ZEND_RESULT_CODE::SUCCESS.as_int(),
+    };
+    match catch_panic(panic_ctx, || hook(ctx)) {
+        Some(Ok(())) => zend::ZEND_RESULT_CODE::SUCCESS.as_int(),
+        Some(Err(err)) => {
+            report(level, err.to_string());
+            zend::ZEND_RESULT_CODE::FAILURE.as_int()
+        },
+        None => zend::ZEND_RESULT_CODE::FAILURE.as_int(),
+    }
+}
+
+/// Maps the return code of a `StartupFunc`/`ShutdownFunc` to a hook result
+fn legacy_result(code: c_int) -> LifecycleResult {
+    if code == zend::ZEND_RESULT_CODE::SUCCESS.as_int() {
+        Ok(())
+    } else {
+        Err("callback returned FAILURE".into())
+    }
+}
+
+extern "C" fn module_startup(type_: c_int, module_number: c_int) -> c_int {
+    let ctx = ModuleContext::new(StartupType::from_raw(type_), module_number);
+    let (startup, classes, ini_entries) = {
+        let mut hooks = hooks();
+        hooks.context = Some(ctx);
+        (hooks.module_startup.clone(), mem::take(&mut hooks.classes), mem::take(&mut hooks.ini_entries))
+    };
+
+    if !ini_entries.is_empty() {
+        let mut defs: Vec<_> = ini_entries.into_iter().map(|def| def.0).collect();
+        defs.push(unsafe { mem::zeroed() });
+        if unsafe { zend::zend_register_ini_entries(defs.as_ptr(), module_number) } != zend::ZEND_RESULT_CODE::SUCCESS.as_int() {
+            return zend::ZEND_RESULT_CODE::FAILURE.as_int();
+        }
+        hooks().ini_registered = true;
+    }
+
+    let registered = catch_panic(PanicContext::Startup, || {
+        for class in classes {
+            if let Err(err) = class.register() {
+                report(zend::E_CORE_WARNING, err.to_string());
+                return false;
+            }
+        }
+        true
+    });
+    if registered != Some(true) {
+        return zend::ZEND_RESULT_CODE::FAILURE.as_int();
+    }
+
+    run_hook(startup, &ctx, PanicContext::Startup, zend::E_CORE_WARNING)
+}
+
+extern "C" fn module_shutdown(type_: c_int, module_number: c_int) -> c_int {
+    let ctx = ModuleContext::new(StartupType::from_raw(type_), module_number);
+    let (shutdown, ini_registered) = {
+        let hooks = hooks();
+        (hooks.module_shutdown.clone(), hooks.ini_registered)
+    };
+    let result = run_hook(shutdown, &ctx, PanicContext::Shutdown, zend::E_CORE_WARNING);
+    if ini_registered {
+        unsafe { zend::zend_unregister_ini_entries(module_number) };
+    }
+    result
+}
+
+extern "C" fn request_startup(type_: c_int, module_number: c_int) -> c_int {
+    let ctx = ModuleContext::new(StartupType::from_raw(type_), module_number);
+    let startup = hooks().request_startup.clone();
+    run_hook(startup, &ctx, PanicContext::Request, zend::E_WARNING)
+}
+
+extern "C" fn request_shutdown(type_: c_int, module_number: c_int) -> c_int {
+    let ctx = ModuleContext::new(StartupType::from_raw(type_), module_number);
+    let shutdown = hooks().request_shutdown.clone();
+    run_hook(shutdown, &ctx, PanicContext::Request, zend::E_WARNING)
+}
+
+extern "C" fn post_deactivate() -> c_int {
+    let (ctx, deactivate) = {
+        let hooks = hooks();
+        (hooks.context, hooks.post_deactivate.clone())
+    };
+    match ctx {
+        Some(ctx) => run_hook(deactivate, &ctx, PanicContext::Request, zend::E_CORE_WARNING),
+        None => zend::ZEND_RESULT_CODE::SUCCESS.as_int(),
+    }
+}
+
+/// Calls the info function, then displays the INI entries like DISPLAY_INI_ENTRIES()
+extern "C" fn module_info(zend_module: *mut Module) {
+    let (info, default_info, ini_registered) = {
+        let hooks = hooks();
+        (hooks.info.clone(), hooks.default_info, hooks.ini_registered)
+    };
+    if default_info {
+        catch_panic(PanicContext::Request, || unsafe { &*zend_module }.default_info_table().render());
+    }
+    if let Some(info) = info {
+        catch_panic(PanicContext::Request, || info());
+    }
+    if ini_registered {
+        unsafe { zend::display_ini_entries(zend_module) };
+    }
+}
+
+pub const MODULE_DEP_REQUIRED: c_uchar = 1;
+pub const MODULE_DEP_CONFLICTS: c_uchar = 2;
+pub const MODULE_DEP_OPTIONAL: c_uchar = 3;
+
+/// Version relation of a dependency, informational: the engine only checks
+/// that the module is loaded
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VersionRel {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
+
+impl VersionRel {
+    fn as_c_str(self) -> *const c_char {
+        match self {
+            VersionRel::Lt => c_str!("lt"),
+            VersionRel::Le => c_str!("le"),
+            VersionRel::Eq => c_str!("eq"),
+            VersionRel::Ge => c_str!("ge"),
+            VersionRel::Gt => c_str!("gt"),
+        }
+    }
+}
 
-pub struct INI {}
+/// zend_module_dep
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ModuleDep {
+    name: *const c_char,
+    rel: *const c_char,
+    version: *const c_char,
+    type_: c_uchar,
+}
+
+impl ModuleDep {
+    /// The module has to be loaded first, the engine refuses to start otherwise
+    pub fn required(name: &str) -> Self {
+        Self::new(name, MODULE_DEP_REQUIRED)
+    }
+
+    /// The module is started first if it's loaded
+    pub fn optional(name: &str) -> Self {
+        Self::new(name, MODULE_DEP_OPTIONAL)
+    }
+
+    /// The engine refuses to start if the module is loaded
+    pub fn conflicts(name: &str) -> Self {
+        Self::new(name, MODULE_DEP_CONFLICTS)
+    }
+
+    fn new(name: &str, type_: c_uchar) -> Self {
+        ModuleDep {
+            name: leak_c_str(name),
+            rel: std::ptr::null(),
+            version: std::ptr::null(),
+            type_,
+        }
+    }
+
+    pub fn with_version(mut self, rel: VersionRel, version: &str) -> Self {
+        self.rel = rel.as_c_str();
+        self.version = leak_c_str(version);
+        self
+    }
+
+    pub fn end() -> Self {
+        ModuleDep {
+            name: std::ptr::null(),
+            rel: std::ptr::null(),
+            version: std::ptr::null(),
+            type_: 0,
+        }
+    }
+}
+
+/// Only points to leaked or static strings
+unsafe impl Send for ModuleDep {}
+
+/// Module entry strings live as long as the process
+fn leak_c_str(s: &str) -> *const c_char {
+    CString::new(s.replace('\0', "")).unwrap_or_default().into_raw()
+}
 
 #[repr(C)]
 pub struct Module {
@@ -18,8 +306,8 @@ pub struct Module {
     zend_debug: c_uchar,
     zts: c_uchar,
 
-    /// Unused
-    ini_entry: *const INI,
+    /// Unused by the engine, INI entries added with `add_ini_entry` are registered on startup
+    ini_entry: *const zend::zend_ini_entry,
     /// Module dependencies
     deps: *const ModuleDep,
     /// Module name
@@ -33,6 +321,7 @@ pub struct Module {
     info_func: Option<InfoFunc>,
     version: *const c_char,
     globals_size: size_t,
+    /// `ts_rsrc_id *globals_id_ptr` in ZTS builds
     globals_ptr: *const c_void,
     globals_ctor: Option<GlobalsCtorFunc>,
     globals_dtor: Option<GlobalsDtorFunc>,
@@ -52,9 +341,9 @@ impl Module {
     pub fn new_raw(name: *const c_char, version: *const c_char) -> Self {
         Module {
             size: mem::size_of::<Module>() as u16,
-            zend_api: 20151012,
-            zend_debug: 0,
-            zts: 0,
+            zend_api: ZEND_MODULE_API_NO,
+            zend_debug: cfg!(php_debug) as c_uchar,
+            zts: cfg!(php_zts) as c_uchar,
             ini_entry: std::ptr::null(),
             deps: std::ptr::null(),
             name: name,
@@ -74,20 +363,146 @@ impl Module {
             type_: 0,
             handle: std::ptr::null(),
             module_number: 0,
-            build_id: c_str!("API20151012,NTS"),
+            build_id: ZEND_MODULE_BUILD_ID.as_ptr() as *const c_char,
         }
     }
 
+    /// MINIT callback, called after the classes added to the module are registered.
+    /// Panics are reported as a fatal error.
+    pub fn on_module_startup<F>(&mut self, func: F)
+    where
+        F: Fn(&ModuleContext) -> LifecycleResult + Send + Sync + 'static,
+    {
+        hooks().module_startup = Some(Arc::new(func));
+        self.module_startup_func = Some(module_startup);
+    }
+
+    /// MSHUTDOWN callback
+    pub fn on_module_shutdown<F>(&mut self, func: F)
+    where
+        F: Fn(&ModuleContext) -> LifecycleResult + Send + Sync + 'static,
+    {
+        hooks().module_shutdown = Some(Arc::new(func));
+        self.module_shutdown_func = Some(module_shutdown);
+    }
+
+    #[deprecated(note = "use `on_module_startup`")]
     pub fn set_startup_func(&mut self, func: StartupFunc) {
-        self.module_startup_func = Some(func);
+        self.on_module_startup(move |ctx| legacy_result(func(ctx.startup_type() as c_int, ctx.module_number())));
     }
 
+    #[deprecated(note = "use `on_module_shutdown`")]
     pub fn set_shutdown_func(&mut self, func: ShutdownFunc) {
-        self.module_shutdown_func = Some(func);
+        self.on_module_shutdown(move |ctx| legacy_result(func(ctx.startup_type() as c_int, ctx.module_number())));
     }
 
-    pub fn set_info_func(&mut self, func: InfoFunc) {
-        self.info_func = Some(func);
+    /// RINIT callback, called before every request
+    pub fn on_request_startup<F>(&mut self, func: F)
+    where
+        F: Fn(&ModuleContext) -> LifecycleResult + Send + Sync + 'static,
+    {
+        hooks().request_startup = Some(Arc::new(func));
+        self.request_startup_func = Some(request_startup);
+    }
+
+    /// RSHUTDOWN callback, called after every request
+    pub fn on_request_shutdown<F>(&mut self, func: F)
+    where
+        F: Fn(&ModuleContext) -> LifecycleResult + Send + Sync + 'static,
+    {
+        hooks().request_shutdown = Some(Arc::new(func));
+        self.request_shutdown_func = Some(request_shutdown);
+    }
+
+    /// Called after every request, once the executor is shut down
+    pub fn on_post_deactivate<F>(&mut self, func: F)
+    where
+        F: Fn(&ModuleContext) -> LifecycleResult + Send + Sync + 'static,
+    {
+        hooks().post_deactivate = Some(Arc::new(func));
+        self.post_deactivate_func = Some(post_deactivate);
+        // post_deactivate only gets its context from module startup
+        self.module_startup_func = Some(module_startup);
+    }
+
+    /// Sets all lifecycle callbacks at once
+    pub fn set_lifecycle<L: ExtensionLifecycle>(&mut self, lifecycle: L) {
+        let lifecycle = Arc::new(lifecycle);
+        let l = lifecycle.clone();
+        self.on_module_startup(move |ctx| l.module_startup(ctx));
+        let l = lifecycle.clone();
+        self.on_module_shutdown(move |ctx| l.module_shutdown(ctx));
+        let l = lifecycle.clone();
+        self.on_request_startup(move |ctx| l.request_startup(ctx));
+        let l = lifecycle.clone();
+        self.on_request_shutdown(move |ctx| l.request_shutdown(ctx));
+        self.on_post_deactivate(move |ctx| lifecycle.post_deactivate(ctx));
+    }
+
+    /// Registers `globals` as the module globals, constructed when the module is registered
+    pub fn set_globals<T: Default + 'static>(&mut self, globals: &'static ModuleGlobals<T>) {
+        self.globals_size = ModuleGlobals::<T>::size();
+        self.globals_ptr = globals.register();
+        self.globals_ctor = Some(ModuleGlobals::<T>::ctor);
+        self.globals_dtor = Some(ModuleGlobals::<T>::dtor);
+    }
+
+    /// INI directive registered on module startup and unregistered on shutdown,
+    /// phpinfo() displays it after the info function output. Fails if the default value is invalid.
+    pub fn add_ini_entry<T: IniValue>(&mut self, entry: &'static IniEntry<T>) -> Result<(), IniError> {
+        entry.validate()?;
+        hooks().ini_entries.push(entry.def());
+        self.module_startup_func = Some(module_startup);
+        self.module_shutdown_func = Some(module_shutdown);
+        self.info_func = Some(module_info);
+        Ok(())
+    }
+
+    /// phpinfo() section, usually rendered with `InfoTable`
+    pub fn on_info<F: Fn() + Send + Sync + 'static>(&mut self, func: F) {
+        hooks().info = Some(Arc::new(func));
+        self.info_func = Some(module_info);
+    }
+
+    pub fn set_info_func(&mut self, func: extern "C" fn()) {
+        self.on_info(move || func());
+    }
+
+    /// Renders a table with the module version and build features in phpinfo(),
+    /// before the output of the info function
+    pub fn set_default_info(&mut self) {
+        hooks().default_info = true;
+        self.info_func = Some(module_info);
+    }
+
+    fn default_info_table(&self) -> InfoTable {
+        let name = unsafe { CStr::from_ptr(self.name) }.to_string_lossy();
+        let version = if self.version.is_null() {
+            "".into()
+        } else {
+            unsafe { CStr::from_ptr(self.version) }.to_string_lossy()
+        };
+        let enabled = |flag| if flag != 0 { "enabled" } else { "disabled" };
+        InfoTable::new()
+            .header([format!("{} support", name).as_str(), "enabled"])
+            .row(["Version", &version])
+            .row(["Thread Safety", enabled(self.zts)])
+            .row(["Debug Build", enabled(self.zend_debug)])
+    }
+
+    /// Dependency checked when the module is registered, it also affects the startup order
+    pub fn add_dependency(&mut self, dep: ModuleDep) {
+        let mut hooks = hooks();
+        hooks.deps.pop();
+        hooks.deps.push(dep);
+        hooks.deps.push(ModuleDep::end());
+        self.deps = hooks.deps.as_ptr();
+    }
+
+    /// Class to be registered on module startup, before the startup function is called
+    pub fn add_class(&mut self, class: ClassBuilder) {
+        hooks().classes.push(class);
+        self.module_startup_func = Some(module_startup);
     }
 
     pub fn set_functions(&mut self, funcs: Box<[Function]>) {
@@ -99,4 +514,31 @@ impl Module {
     }
 }
 
-unsafe impl Sync for Module {}
\ No newline at end of file
+unsafe impl Sync for Module {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zend_module_entry_layout() {
+        // zend_module_entry and zend_module_dep are unchanged from PHP 7.0 to 8.3
+        assert_eq!(mem::size_of::<Module>(), 168, concat!("Size of: ", stringify!(Module)));
+        assert_eq!(mem::size_of::<ModuleDep>(), 32, concat!("Size of: ", stringify!(ModuleDep)));
+        let module = mem::MaybeUninit::<Module>::uninit();
+        let base = module.as_ptr();
+        let offset = unsafe { std::ptr::addr_of!((*base).build_id) as usize - base as usize };
+        assert_eq!(offset, 160, "Offset of: zend_module_entry::build_id");
+    }
+
+    #[test]
+    fn zend_module_build_id() {
+        let build_id = CStr::from_bytes_with_nul(ZEND_MODULE_BUILD_ID.as_bytes()).unwrap();
+        let expected = format!(
+            "API{}{}{}",
+            ZEND_MODULE_API_NO,
+            if cfg!(php_zts) { ",TS" } else { ",NTS" },
+            if cfg!(php_debug) { ",debug" } else { "" },
+        );
+        assert_eq!(build_id.to_str().unwrap(), expected);
+    }
+}
@@ -0,0 +1,122 @@
+use std::os::raw::{c_int, c_void};
+use crate::zend::{
+    zval, zend_object, zend_string, zend_function, zend_class_entry, zend_long, zend_uchar, HashTable,
+};
+#[cfg(not(feature = "php80"))]
+use crate::zend::zend_execute_data;
+
+pub type ObjectHandlers = zend_object_handlers;
+
+/// Object argument of most handlers: its zval before PHP 8.0, the object itself since
+#[cfg(not(feature = "php80"))]
+pub type zend_object_handler_obj = *mut zval;
+#[cfg(feature = "php80")]
+pub type zend_object_handler_obj = *mut zend_object;
+
+pub type zend_object_clone_obj_t = Option<unsafe extern "C" fn(object: zend_object_handler_obj) -> *mut zend_object>;
+
+/// zend_prop_purpose of get_properties_for
+#[cfg(feature = "php74")]
+pub type zend_prop_purpose = c_int;
+
+#[cfg(not(feature = "php80"))]
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct zend_object_handlers {
+    /// offset of real object header (usually zero)
+    pub offset: c_int,
+    /* general object functions */
+    pub free_obj: Option<unsafe extern "C" fn(object: *mut zend_object)>,
+    pub dtor_obj: Option<unsafe extern "C" fn(object: *mut zend_object)>,
+    pub clone_obj: zend_object_clone_obj_t,
+    /* individual object functions */
+    pub read_property: Option<unsafe extern "C" fn(object: *mut zval, member: *mut zval, type_: c_int, cache_slot: *mut *mut c_void, rv: *mut zval) -> *mut zval>,
+    #[cfg(not(feature = "php74"))]
+    pub write_property: Option<unsafe extern "C" fn(object: *mut zval, member: *mut zval, value: *mut zval, cache_slot: *mut *mut c_void)>,
+    #[cfg(feature = "php74")]
+    pub write_property: Option<unsafe extern "C" fn(object: *mut zval, member: *mut zval, value: *mut zval, cache_slot: *mut *mut c_void) -> *mut zval>,
+    pub read_dimension: Option<unsafe extern "C" fn(object: *mut zval, offset: *mut zval, type_: c_int, rv: *mut zval) -> *mut zval>,
+    pub write_dimension: Option<unsafe extern "C" fn(object: *mut zval, offset: *mut zval, value: *mut zval)>,
+    pub get_property_ptr_ptr: Option<unsafe extern "C" fn(object: *mut zval, member: *mut zval, type_: c_int, cache_slot: *mut *mut c_void) -> *mut zval>,
+    pub get: Option<unsafe extern "C" fn(object: *mut zval, rv: *mut zval) -> *mut zval>,
+    pub set: Option<unsafe extern "C" fn(object: *mut zval, value: *mut zval)>,
+    pub has_property: Option<unsafe extern "C" fn(object: *mut zval, member: *mut zval, has_set_exists: c_int, cache_slot: *mut *mut c_void) -> c_int>,
+    pub unset_property: Option<unsafe extern "C" fn(object: *mut zval, member: *mut zval, cache_slot: *mut *mut c_void)>,
+    pub has_dimension: Option<unsafe extern "C" fn(object: *mut zval, member: *mut zval, check_empty: c_int) -> c_int>,
+    pub unset_dimension: Option<unsafe extern "C" fn(object: *mut zval, offset: *mut zval)>,
+    pub get_properties: Option<unsafe extern "C" fn(object: *mut zval) -> *mut HashTable>,
+    pub get_method: Option<unsafe extern "C" fn(object: *mut *mut zend_object, method: *mut zend_string, key: *const zval) -> *mut zend_function>,
+    pub call_method: Option<unsafe extern "C" fn(method: *mut zend_string, object: *mut zend_object, execute_data: *mut zend_execute_data, return_value: *mut zval) -> c_int>,
+    pub get_constructor: Option<unsafe extern "C" fn(object: *mut zend_object) -> *mut zend_function>,
+    pub get_class_name: Option<unsafe extern "C" fn(object: *const zend_object) -> *mut zend_string>,
+    pub compare_objects: Option<unsafe extern "C" fn(object1: *mut zval, object2: *mut zval) -> c_int>,
+    pub cast_object: Option<unsafe extern "C" fn(readobj: *mut zval, retval: *mut zval, type_: c_int) -> c_int>,
+    pub count_elements: Option<unsafe extern "C" fn(object: *mut zval, count: *mut zend_long) -> c_int>,
+    pub get_debug_info: Option<unsafe extern "C" fn(object: *mut zval, is_temp: *mut c_int) -> *mut HashTable>,
+    pub get_closure: Option<unsafe extern "C" fn(obj: *mut zval, ce_ptr: *mut *mut zend_class_entry, fptr_ptr: *mut *mut zend_function, obj_ptr: *mut *mut zend_object) -> c_int>,
+    pub get_gc: Option<unsafe extern "C" fn(object: *mut zval, table: *mut *mut zval, n: *mut c_int) -> *mut HashTable>,
+    pub do_operation: Option<unsafe extern "C" fn(opcode: zend_uchar, result: *mut zval, op1: *mut zval, op2: *mut zval) -> c_int>,
+    pub compare: Option<unsafe extern "C" fn(result: *mut zval, op1: *mut zval, op2: *mut zval) -> c_int>,
+    #[cfg(feature = "php74")]
+    pub get_properties_for: Option<unsafe extern "C" fn(object: *mut zval, purpose: zend_prop_purpose) -> *mut HashTable>,
+}
+
+/// PHP 8.0 passes `zend_object` and property names as `zend_string`, get/set, call_method
+/// and compare_objects are gone
+#[cfg(feature = "php80")]
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct zend_object_handlers {
+    /// offset of real object header (usually zero)
+    pub offset: c_int,
+    /* object handlers */
+    pub free_obj: Option<unsafe extern "C" fn(object: *mut zend_object)>,
+    pub dtor_obj: Option<unsafe extern "C" fn(object: *mut zend_object)>,
+    pub clone_obj: zend_object_clone_obj_t,
+    /* individual object functions */
+    pub read_property: Option<unsafe extern "C" fn(object: *mut zend_object, member: *mut zend_string, type_: c_int, cache_slot: *mut *mut c_void, rv: *mut zval) -> *mut zval>,
+    pub write_property: Option<unsafe extern "C" fn(object: *mut zend_object, member: *mut zend_string, value: *mut zval, cache_slot: *mut *mut c_void) -> *mut zval>,
+    pub read_dimension: Option<unsafe extern "C" fn(object: *mut zend_object, offset: *mut zval, type_: c_int, rv: *mut zval) -> *mut zval>,
+    pub write_dimension: Option<unsafe extern "C" fn(object: *mut zend_object, offset: *mut zval, value: *mut zval)>,
+    pub get_property_ptr_ptr: Option<unsafe extern "C" fn(object: *mut zend_object, member: *mut zend_string, type_: c_int, cache_slot: *mut *mut c_void) -> *mut zval>,
+    pub has_property: Option<unsafe extern "C" fn(object: *mut zend_object, member: *mut zend_string, has_set_exists: c_int, cache_slot: *mut *mut c_void) -> c_int>,
+    pub unset_property: Option<unsafe extern "C" fn(object: *mut zend_object, member: *mut zend_string, cache_slot: *mut *mut c_void)>,
+    pub has_dimension: Option<unsafe extern "C" fn(object: *mut zend_object, member: *mut zval, check_empty: c_int) -> c_int>,
+    pub unset_dimension: Option<unsafe extern "C" fn(object: *mut zend_object, offset: *mut zval)>,
+    pub get_properties: Option<unsafe extern "C" fn(object: *mut zend_object) -> *mut HashTable>,
+    pub get_method: Option<unsafe extern "C" fn(object: *mut *mut zend_object, method: *mut zend_string, key: *const zval) -> *mut zend_function>,
+    pub get_constructor: Option<unsafe extern "C" fn(object: *mut zend_object) -> *mut zend_function>,
+    pub get_class_name: Option<unsafe extern "C" fn(object: *const zend_object) -> *mut zend_string>,
+    pub cast_object: Option<unsafe extern "C" fn(readobj: *mut zend_object, retval: *mut zval, type_: c_int) -> c_int>,
+    pub count_elements: Option<unsafe extern "C" fn(object: *mut zend_object, count: *mut zend_long) -> c_int>,
+    pub get_debug_info: Option<unsafe extern "C" fn(object: *mut zend_object, is_temp: *mut c_int) -> *mut HashTable>,
+    pub get_closure: Option<unsafe extern "C" fn(obj: *mut zend_object, ce_ptr: *mut *mut zend_class_entry, fptr_ptr: *mut *mut zend_function, obj_ptr: *mut *mut zend_object, check_only: bool) -> c_int>,
+    pub get_gc: Option<unsafe extern "C" fn(object: *mut zend_object, table: *mut *mut zval, n: *mut c_int) -> *mut HashTable>,
+    pub do_operation: Option<unsafe extern "C" fn(opcode: zend_uchar, result: *mut zval, op1: *mut zval, op2: *mut zval) -> c_int>,
+    pub compare: Option<unsafe extern "C" fn(object1: *mut zval, object2: *mut zval) -> c_int>,
+    pub get_properties_for: Option<unsafe extern "C" fn(object: *mut zend_object, purpose: zend_prop_purpose) -> *mut HashTable>,
+}
+
+extern "C" {
+    pub static std_object_handlers: zend_object_handlers;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem;
+
+    #[test]
+    fn zend_object_handlers_layout() {
+        let size = if cfg!(feature = "php80") {
+            200
+        } else if cfg!(feature = "php74") {
+            232
+        } else {
+            224
+        };
+        assert_eq!(
+            mem::size_of::<zend_object_handlers>(), size, concat!("Size of: ", stringify!(zend_object_handlers))
+        );
+    }
+}
@@ -0,0 +1,25 @@
+use std::mem;
+use crate::zend::{zval, zend_object, zend_class_entry, zend_object_handler_obj, ZEND_ACC_USE_GUARDS};
+
+extern "C" {
+    pub fn zend_object_std_init(object: *mut zend_object, ce: *mut zend_class_entry);
+    pub fn zend_object_std_dtor(object: *mut zend_object);
+    pub fn zend_objects_new(ce: *mut zend_class_entry) -> *mut zend_object;
+    pub fn zend_objects_destroy_object(object: *mut zend_object);
+    pub fn zend_objects_clone_members(new_object: *mut zend_object, old_object: *mut zend_object);
+    pub fn zend_objects_clone_obj(object: zend_object_handler_obj) -> *mut zend_object;
+
+    pub fn object_properties_init(object: *mut zend_object, class_type: *mut zend_class_entry);
+    pub fn object_init_ex(arg: *mut zval, ce: *mut zend_class_entry) -> i32;
+}
+
+/// Size of the properties table following zend_object, the first slot is already a part of it,
+/// so the result may wrap around like in C
+///
+/// # Safety
+/// `ce` must point to a valid class entry
+#[inline]
+pub unsafe fn zend_object_properties_size(ce: *const zend_class_entry) -> usize {
+    let guards = if (*ce).ce_flags & ZEND_ACC_USE_GUARDS != 0 { 0 } else { 1 };
+    mem::size_of::<zval>().wrapping_mul(((*ce).default_properties_count - guards) as usize)
+}
@@ -0,0 +1,39 @@
+use std::os::raw::c_int;
+#[cfg(not(feature = "php80"))]
+use crate::zend::{IS_LONG, zend_type};
+use crate::zend::zval;
+
+extern "C" {
+    #[cfg(feature = "php80")]
+    fn zend_compare(op1: *mut zval, op2: *mut zval) -> c_int;
+    #[cfg(not(feature = "php80"))]
+    fn compare_function(result: *mut zval, op1: *mut zval, op2: *mut zval) -> c_int;
+}
+
+impl zval {
+    /// PHP comparison (`<=>`): -1, 0 or 1. May throw for uncomparable objects.
+    ///
+    /// # Safety
+    /// `op1` and `op2` must point to valid zvals
+    #[cfg(feature = "php80")]
+    #[inline]
+    pub unsafe fn compare(op1: *mut zval, op2: *mut zval) -> c_int {
+        zend_compare(op1, op2)
+    }
+
+    /// PHP comparison (`<=>`): -1, 0 or 1. May throw for uncomparable objects.
+    ///
+    /// # Safety
+    /// `op1` and `op2` must point to valid zvals
+    #[cfg(not(feature = "php80"))]
+    #[inline]
+    pub unsafe fn compare(op1: *mut zval, op2: *mut zval) -> c_int {
+        let mut result = zval::default();
+        compare_function(&mut result, op1, op2);
+        if result.get_type() as zend_type == IS_LONG {
+            result.value.lval.signum() as c_int
+        } else {
+            0
+        }
+    }
+}
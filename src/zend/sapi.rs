@@ -0,0 +1,77 @@
+use std::os::raw::{c_char, c_int, c_uint, c_void};
+use crate::zend::zend_function_entry;
+
+/// main/SAPI.h, callbacks are kept as plain pointers since they are not called from Rust.
+/// Only accessed through `sapi_module`, so fields appended by later versions are left out.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct sapi_module_struct {
+    pub name: *mut c_char,
+    pub pretty_name: *mut c_char,
+
+    pub startup: *mut c_void,
+    pub shutdown: *mut c_void,
+    pub activate: *mut c_void,
+    pub deactivate: *mut c_void,
+    pub ub_write: *mut c_void,
+    pub flush: *mut c_void,
+    pub get_stat: *mut c_void,
+    pub getenv: *mut c_void,
+    pub sapi_error: *mut c_void,
+    pub header_handler: *mut c_void,
+    pub send_headers: *mut c_void,
+    pub send_header: *mut c_void,
+    pub read_post: *mut c_void,
+    pub read_cookies: *mut c_void,
+    pub register_server_variables: *mut c_void,
+    pub log_message: *mut c_void,
+    pub get_request_time: *mut c_void,
+    pub terminate_process: *mut c_void,
+
+    pub php_ini_path_override: *mut c_char,
+
+    #[cfg(not(feature = "php74"))]
+    pub block_interruptions: *mut c_void,
+    #[cfg(not(feature = "php74"))]
+    pub unblock_interruptions: *mut c_void,
+    pub default_post_reader: *mut c_void,
+    pub treat_data: *mut c_void,
+    pub executable_location: *mut c_char,
+
+    pub php_ini_ignore: c_int,
+    pub php_ini_ignore_cwd: c_int,
+
+    pub get_fd: *mut c_void,
+    pub force_http_10: *mut c_void,
+    pub get_target_uid: *mut c_void,
+    pub get_target_gid: *mut c_void,
+    pub input_filter: *mut c_void,
+    pub ini_defaults: *mut c_void,
+    pub phpinfo_as_text: c_int,
+
+    pub ini_entries: *mut c_char,
+    pub additional_functions: *const zend_function_entry,
+    pub input_filter_init: Option<unsafe extern "C" fn() -> c_uint>,
+}
+
+extern "C" {
+    pub static mut sapi_module: sapi_module_struct;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem;
+
+    #[test]
+    fn sapi_module_struct_layout() {
+        let (size, phpinfo_as_text) = if cfg!(feature = "php74") { (280, 248) } else { (296, 264) };
+        assert_eq!(
+            mem::size_of::<sapi_module_struct>(), size, concat!("Size of: ", stringify!(sapi_module_struct))
+        );
+        let sapi = mem::MaybeUninit::<sapi_module_struct>::uninit();
+        let base = sapi.as_ptr();
+        let offset = unsafe { std::ptr::addr_of!((*base).phpinfo_as_text) as usize - base as usize };
+        assert_eq!(offset, phpinfo_as_text, "Offset of: sapi_module_struct::phpinfo_as_text");
+    }
+}
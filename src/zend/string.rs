@@ -1,7 +1,25 @@
-use std::{mem, ptr};
-use crate::zend::{zend_string, Flag, pemalloc, ZEND_MM_ALIGNED_SIZE, IS_STRING, IS_STR_PERSISTENT};
+use std::{mem, ptr, slice};
+use crate::zend::{
+    zend_string, zend_ulong, Flag, pemalloc, pefree, ZEND_MM_ALIGNED_SIZE, GC_STRING, GC_FLAGS_SHIFT,
+    IS_STR_PERSISTENT, IS_STR_INTERNED, IS_STR_PERMANENT,
+};
+
+extern "C" {
+    pub static mut zend_new_interned_string: Option<unsafe extern "C" fn(str: *mut zend_string) -> *mut zend_string>;
+}
+
+/// zend_inline_hash_func: DJBX33A (Daniel J. Bernstein, Times 33 with Addition).
+/// The high bit is always set so a computed hash is never 0, which means "not computed yet".
+#[inline]
+pub fn zend_inline_hash_func(bytes: &[u8]) -> zend_ulong {
+    let hash = bytes.iter().fold(5381 as zend_ulong, |hash, &c| {
+        hash.wrapping_mul(33).wrapping_add(c as zend_ulong)
+    });
+    hash | !(zend_ulong::MAX >> 1)
+}
 
 impl zend_string {
+    #[track_caller]
     #[inline]
     pub fn alloc(len: usize, persistent: bool) -> *mut Self {
         let ret = pemalloc::<Self>(
@@ -11,23 +29,119 @@ impl zend_string {
 
         unsafe {
             (*ret).gc.refcount = 1;
-            (*ret).gc.u.type_info = IS_STRING as Flag
-                | (if persistent {IS_STR_PERSISTENT} else {0} << 8);
+            (*ret).gc.u.type_info = GC_STRING
+                | (if persistent {IS_STR_PERSISTENT} else {0} << GC_FLAGS_SHIFT);
             (*ret).h = 0;
             (*ret).len = len;
         };
         ret
     }
 
+    #[track_caller]
     #[inline]
     pub fn init(src: &str, persistent: bool) -> *mut Self {
+        Self::init_bytes(src.as_bytes(), persistent)
+    }
+
+    /// zend_string_init, `src` may contain NUL bytes
+    #[track_caller]
+    #[inline]
+    pub fn init_bytes(src: &[u8], persistent: bool) -> *mut Self {
         let len = src.len();
         let ret = Self::alloc(len, persistent);
 
         unsafe {
             ptr::copy_nonoverlapping(src.as_ptr(), (*ret).val.as_mut_ptr(), len);
-            *(*ret).val.get_unchecked_mut(len) = b'\0';
+            *(*ret).val.as_mut_ptr().add(len) = b'\0';
         }
         ret
     }
-}
\ No newline at end of file
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.val.as_ptr(), self.len) }
+    }
+
+    /// zend_string_hash_val: computes the hash on first use and caches it in `h`
+    #[inline]
+    pub fn hash_val(&mut self) -> zend_ulong {
+        if self.h == 0 {
+            self.h = zend_inline_hash_func(self.as_bytes());
+        }
+        self.h
+    }
+
+    /// Marks a persistent string as interned and permanent: it's never refcounted nor freed
+    ///
+    /// # Safety
+    /// The string must be persistent and must not be shared yet
+    #[inline]
+    pub unsafe fn make_permanent(s: *mut Self) {
+        (*s).hash_val();
+        (*s).gc.u.type_info |= (IS_STR_INTERNED | IS_STR_PERMANENT) << GC_FLAGS_SHIFT;
+    }
+
+    #[inline]
+    fn has_flag(&self, flag: Flag) -> bool {
+        unsafe { self.gc.u.type_info & (flag << GC_FLAGS_SHIFT) != 0 }
+    }
+
+    /// ZSTR_IS_INTERNED
+    #[inline]
+    pub fn is_interned(&self) -> bool {
+        self.has_flag(IS_STR_INTERNED)
+    }
+
+    #[inline]
+    pub fn is_persistent(&self) -> bool {
+        self.has_flag(IS_STR_PERSISTENT)
+    }
+
+    /// zend_string_addref, interned strings are not refcounted
+    #[inline]
+    pub fn addref(&mut self) {
+        if !self.is_interned() {
+            self.gc.refcount += 1;
+        }
+    }
+
+    /// zend_string_copy
+    ///
+    /// # Safety
+    /// `s` must point to a valid string
+    #[inline]
+    pub unsafe fn copy(s: *mut Self) -> *mut Self {
+        (*s).addref();
+        s
+    }
+
+    /// zend_string_release, frees the string when the last reference is released
+    ///
+    /// # Safety
+    /// `s` must point to a valid string owning one reference, it may be freed
+    #[track_caller]
+    #[inline]
+    pub unsafe fn release(s: *mut Self) {
+        if !(*s).is_interned() {
+            (*s).gc.refcount -= 1;
+            if (*s).gc.refcount == 0 {
+                pefree(s, (*s).is_persistent());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn djbx33a_hash() {
+        let high_bit = !(zend_ulong::MAX >> 1);
+        assert_eq!(zend_inline_hash_func(b""), 5381 | high_bit);
+        assert_eq!(zend_inline_hash_func(b"a"), 177670 | high_bit);
+        assert_eq!(zend_inline_hash_func(b"ab"), 5863208 | high_bit);
+        assert_ne!(zend_inline_hash_func(b"ab"), zend_inline_hash_func(b"ba"));
+        assert_ne!(zend_inline_hash_func(&[0; 64]), 0);
+    }
+}
@@ -0,0 +1,26 @@
+use std::os::raw::c_int;
+#[cfg(php_zts)]
+use std::os::raw::c_void;
+
+pub type ts_rsrc_id = c_int;
+
+#[inline]
+pub const fn TSRM_UNSHUFFLE_RSRC_ID(id: ts_rsrc_id) -> usize {
+    (id - 1) as usize
+}
+
+#[cfg(php_zts)]
+extern "C" {
+    pub fn tsrm_get_ls_cache() -> *mut c_void;
+}
+
+/// TSRMG_BULK: globals of the resource `id` for the current thread
+///
+/// # Safety
+/// `id` must be allocated by ts_allocate_id
+#[cfg(php_zts)]
+#[inline]
+pub unsafe fn TSRMG_BULK(id: ts_rsrc_id) -> *mut c_void {
+    let storage = *(tsrm_get_ls_cache() as *mut *mut *mut c_void);
+    *storage.add(TSRM_UNSHUFFLE_RSRC_ID(id))
+}
@@ -1,6 +1,6 @@
 use std::mem;
 use std::os::raw::{c_uchar, c_char, c_void, c_int};
-use crate::zend::zend_function;
+use crate::zend::{zend_function, zend_class_entry, zend_object_handlers};
 
 /// Export renamed zend types
 pub type Long = zend_long;
@@ -19,6 +19,7 @@ pub type Refcounted = zend_refcounted;
 pub type String = zend_string;
 pub type Array = zend_array;
 pub type Reference = zend_reference;
+pub type Object = zend_object;
 
 pub type Flag = u32;
 pub type VaList = *mut c_char;
@@ -37,73 +38,153 @@ pub const IS_RESOURCE: zend_type = 9;
 pub const IS_REFERENCE: zend_type = 10;
 
 /// constant expressions
+#[cfg(not(feature = "php74"))]
 pub const IS_CONSTANT: zend_type = 11;
+#[cfg(not(feature = "php74"))]
 pub const IS_CONSTANT_AST: zend_type = 12;
+#[cfg(feature = "php74")]
+pub const IS_CONSTANT_AST: zend_type = 11;
 
 /// fake types
+#[cfg(not(feature = "php74"))]
 pub const _IS_BOOL: zend_type = 13;
+#[cfg(all(feature = "php74", not(feature = "php80")))]
+pub const _IS_BOOL: zend_type = 16;
+#[cfg(all(feature = "php80", not(feature = "php81")))]
+pub const _IS_BOOL: zend_type = 17;
+#[cfg(feature = "php81")]
+pub const _IS_BOOL: zend_type = 18;
+#[cfg(not(feature = "php74"))]
 pub const IS_CALLABLE: zend_type = 14;
+#[cfg(all(feature = "php74", not(feature = "php80")))]
+pub const IS_CALLABLE: zend_type = 17;
+#[cfg(feature = "php80")]
+pub const IS_CALLABLE: zend_type = 12;
 
 /// internal types
+#[cfg(not(feature = "php74"))]
 pub const IS_INDIRECT: zend_type = 15;
+#[cfg(not(feature = "php74"))]
 pub const IS_PTR: zend_type = 17;
+#[cfg(all(feature = "php74", not(feature = "php80")))]
+pub const IS_INDIRECT: zend_type = 13;
+#[cfg(all(feature = "php74", not(feature = "php80")))]
+pub const IS_PTR: zend_type = 14;
+#[cfg(feature = "php80")]
+pub const IS_INDIRECT: zend_type = 12;
+#[cfg(feature = "php80")]
+pub const IS_PTR: zend_type = 13;
+#[cfg(feature = "php80")]
+pub const IS_ALIAS_PTR: zend_type = 14;
+#[cfg(feature = "php74")]
+pub const _IS_ERROR: zend_type = 15;
 
 pub const Z_TYPE_MASK: Flag = 0xff;
 
 pub const Z_TYPE_FLAGS_SHIFT: Flag = 8;
+#[cfg(not(feature = "php74"))]
 pub const Z_CONST_FLAGS_SHIFT: Flag = 16;
 
+#[cfg(not(feature = "php74"))]
+pub const GC_FLAGS_SHIFT: Flag = 8;
+#[cfg(not(feature = "php74"))]
+pub const GC_INFO_SHIFT: Flag = 16;
+#[cfg(not(feature = "php74"))]
+pub const GC_INFO_MASK: Flag = 0xffff0000;
+
+/** zval_gc_flags(zval.value->gc.u.type_info) (common flags) */
+#[cfg(not(feature = "php74"))]
+pub const GC_COLLECTABLE: Flag = 1 << 7;
+
+#[cfg(not(feature = "php74"))]
+pub const GC_STRING: Flag = IS_STRING as Flag;
+#[cfg(not(feature = "php74"))]
+pub const GC_ARRAY: Flag = IS_ARRAY as Flag | (GC_COLLECTABLE << GC_FLAGS_SHIFT);
+#[cfg(not(feature = "php74"))]
+pub const GC_OBJECT: Flag = IS_OBJECT as Flag | (GC_COLLECTABLE << GC_FLAGS_SHIFT);
+
+#[cfg(feature = "php74")]
 pub const GC_TYPE_MASK: Flag = 0x0000000f;
+#[cfg(feature = "php74")]
 pub const GC_FLAGS_MASK: Flag = 0x000003f0;
+#[cfg(feature = "php74")]
 pub const GC_INFO_MASK: Flag = 0xfffffc00;
+#[cfg(feature = "php74")]
 pub const GC_FLAGS_SHIFT: Flag = 0;
+#[cfg(feature = "php74")]
 pub const GC_INFO_SHIFT: Flag = 10;
 
-/** zval_gc_flags(zval.value.gc.u.type_info) (common flags) */
-pub const GC_COLLECTABLE: Flag = 1 << 4;
+/** zval_gc_flags(zval.value->gc.u.type_info) (common flags) */
+#[cfg(feature = "php74")]
+pub const GC_NOT_COLLECTABLE: Flag = 1 << 4;
 /// used for recursion detection
+#[cfg(feature = "php74")]
 pub const GC_PROTECTED: Flag = 1 << 5;
 /// can't be canged in place
+#[cfg(feature = "php74")]
 pub const GC_IMMUTABLE: Flag = 1 << 6;
 /// allocated using malloc
+#[cfg(feature = "php74")]
 pub const GC_PERSISTENT: Flag = 1 << 7;
 /// persistent, but thread-local
+#[cfg(feature = "php74")]
 pub const GC_PERSISTENT_LOCAL: Flag = 1 << 8;
 
-pub const GC_ARRAY: Flag = IS_ARRAY as Flag | (GC_COLLECTABLE << GC_FLAGS_SHIFT);
-pub const GC_OBJECT: Flag = IS_OBJECT as Flag | (GC_COLLECTABLE << GC_FLAGS_SHIFT);
+#[cfg(feature = "php74")]
+pub const GC_STRING: Flag = IS_STRING as Flag | (GC_NOT_COLLECTABLE << GC_FLAGS_SHIFT);
+#[cfg(feature = "php74")]
+pub const GC_ARRAY: Flag = IS_ARRAY as Flag;
+#[cfg(feature = "php74")]
+pub const GC_OBJECT: Flag = IS_OBJECT as Flag;
 
 /** zval.u1.v.type_flags */
+#[cfg(not(feature = "php74"))]
 pub const IS_TYPE_CONSTANT: Flag = 1 << 0;
+#[cfg(not(feature = "php74"))]
 pub const IS_TYPE_IMMUTABLE: Flag = 1 << 1;
+#[cfg(not(feature = "php74"))]
 pub const IS_TYPE_REFCOUNTED: Flag = 1 << 2;
+#[cfg(not(feature = "php74"))]
 pub const IS_TYPE_COLLECTABLE: Flag = 1 << 3;
+#[cfg(not(feature = "php74"))]
 pub const IS_TYPE_COPYABLE: Flag = 1 << 4;
+#[cfg(not(feature = "php74"))]
 pub const IS_TYPE_SYMBOLTABLE: Flag = 1 << 5;
 
+#[cfg(feature = "php74")]
+pub const IS_TYPE_REFCOUNTED: Flag = 1 << 0;
+#[cfg(feature = "php74")]
+pub const IS_TYPE_COLLECTABLE: Flag = 1 << 1;
+
 /** extended types */
 pub const IS_INTERNED_STRING_EX: Flag = IS_STRING as Flag;
 
+#[cfg(not(feature = "php74"))]
 pub const IS_STRING_EX: Flag = IS_STRING as Flag | (( IS_TYPE_REFCOUNTED | IS_TYPE_COPYABLE) << Z_TYPE_FLAGS_SHIFT);
+#[cfg(not(feature = "php74"))]
 pub const IS_ARRAY_EX: Flag = 	IS_ARRAY as Flag | (( IS_TYPE_REFCOUNTED | IS_TYPE_COLLECTABLE | IS_TYPE_COPYABLE) << Z_TYPE_FLAGS_SHIFT);
+#[cfg(feature = "php74")]
+pub const IS_STRING_EX: Flag = IS_STRING as Flag | (IS_TYPE_REFCOUNTED << Z_TYPE_FLAGS_SHIFT);
+#[cfg(feature = "php74")]
+pub const IS_ARRAY_EX: Flag = IS_ARRAY as Flag | ((IS_TYPE_REFCOUNTED | IS_TYPE_COLLECTABLE) << Z_TYPE_FLAGS_SHIFT);
 pub const IS_OBJECT_EX: Flag = IS_OBJECT as Flag | (( IS_TYPE_REFCOUNTED | IS_TYPE_COLLECTABLE) << Z_TYPE_FLAGS_SHIFT);
 pub const IS_RESOURCE_EX: Flag = IS_RESOURCE as Flag | (( IS_TYPE_REFCOUNTED ) << Z_TYPE_FLAGS_SHIFT);
 pub const IS_REFERENCE_EX: Flag = IS_REFERENCE as Flag | (( IS_TYPE_REFCOUNTED ) << Z_TYPE_FLAGS_SHIFT);
 
+#[cfg(not(feature = "php74"))]
 pub const IS_CONSTANT_EX: Flag = IS_CONSTANT as Flag | ((IS_TYPE_CONSTANT | IS_TYPE_REFCOUNTED | IS_TYPE_COPYABLE) << Z_TYPE_FLAGS_SHIFT);
+#[cfg(not(feature = "php74"))]
 pub const IS_CONSTANT_AST_EX: Flag = IS_CONSTANT_AST as Flag | ((IS_TYPE_CONSTANT | IS_TYPE_REFCOUNTED | IS_TYPE_COPYABLE) << Z_TYPE_FLAGS_SHIFT);
-
+#[cfg(feature = "php74")]
+pub const IS_CONSTANT_AST_EX: Flag = IS_CONSTANT_AST as Flag | (IS_TYPE_REFCOUNTED << Z_TYPE_FLAGS_SHIFT);
 
 pub type zend_long = isize;
 pub type zend_ulong = usize;
 pub type zend_off_t = isize;
 pub type zend_bool = c_uchar;
 pub type zend_uchar = c_uchar;
-//pub type zend_object_handlers = _zend_object_handlers;
-//pub type zend_class_entry = _zend_class_entry;
 //pub type zend_function = _zend_function;
 //pub type zend_execute_data = _zend_execute_data;
-//pub type zend_object = _zend_object;
 //pub type zend_resource = _zend_resource;
 //pub type zend_ast_ref = _zend_ast_ref;
 //pub type zend_ast = _zend_ast;
@@ -142,13 +223,13 @@ pub union zend_value {
     pub counted: *mut zend_refcounted,
     pub str: *mut zend_string,
     pub arr: *mut zend_array,
-//    pub obj: *mut zend_object,
+    pub obj: *mut zend_object,
 //    pub res: *mut zend_resource,
     pub ref_: *mut zend_reference,
 //    pub ast: *mut zend_ast_ref,
     pub zv: *mut zval,
     pub ptr: *mut c_void,
-//    pub ce: *mut zend_class_entry,
+    pub ce: *mut zend_class_entry,
     pub func: *mut zend_function,
     pub ww: zend_value_ww,
 }
@@ -187,14 +268,20 @@ pub struct zval_v {
     /// active type
     pub type_: zend_uchar,
     pub type_flags: zend_uchar,
+    #[cfg(not(feature = "php74"))]
     pub const_flags: zend_uchar,
     /// call info for EX(This)
+    #[cfg(not(feature = "php74"))]
     pub reserved: zend_uchar,
+    /// call info for EX(This)
+    #[cfg(feature = "php74")]
+    pub extra: u16,
 }
 
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub union zval_u2 {
+    #[cfg(not(feature = "php74"))]
     pub var_flags: u32,
     /// hash collision chain
     pub next: u32,
@@ -208,6 +295,12 @@ pub union zval_u2 {
     pub fe_pos: u32,
     /// foreach iterator index
     pub fe_iter_idx: u32,
+    /// class constant access flags
+    #[cfg(feature = "php74")]
+    pub access_flags: u32,
+    /// not further specified
+    #[cfg(feature = "php74")]
+    pub extra: u32,
 }
 
 impl zval {
@@ -248,11 +341,12 @@ impl zval {
         self.set_type_info(IS_DOUBLE as u32);
     }
 
-//    #[inline]
-//    pub fn set_str(&mut self, value: &mut zend_string) {
-//        self.value.str = value as *mut _;
-//        self.set_type_info();
-//    }
+    /// ZVAL_STR, takes over a reference of `value`
+    #[inline]
+    pub fn set_str(&mut self, value: &mut zend_string) {
+        self.set_type_info(if value.is_interned() { IS_INTERNED_STRING_EX } else { IS_STRING_EX });
+        self.value.str = value as *mut _;
+    }
 
     #[inline]
     pub fn set_arr(&mut self, value: &mut zend_array) {
@@ -260,6 +354,12 @@ impl zval {
         self.set_type_info(IS_ARRAY_EX);
     }
 
+    #[inline]
+    pub fn set_obj(&mut self, value: &mut zend_object) {
+        self.value.obj = value as *mut _;
+        self.set_type_info(IS_OBJECT_EX);
+    }
+
     #[inline]
     pub fn set_new_str(&mut self, src: &str, persistent: bool) {
         self.value.str = zend_string::init(src, persistent);
@@ -302,6 +402,11 @@ impl zval {
         self.get_type() == IS_ARRAY as zend_uchar
     }
 
+    #[inline]
+    pub fn is_object(&self) -> bool {
+        self.get_type() == IS_OBJECT as zend_uchar
+    }
+
     #[inline]
     pub fn indirect(&self) -> *mut Self {
         unsafe { self.value.zv }
@@ -317,6 +422,7 @@ impl zval {
         unsafe { self.u1.v.type_flags }
     }
 
+    #[cfg(not(feature = "php74"))]
     #[inline]
     pub fn get_const_flags(&self) -> zend_uchar {
         unsafe { self.u1.v.const_flags }
@@ -332,6 +438,7 @@ impl zval {
         self.u1.type_info = type_info;
     }
 
+    #[cfg(not(feature = "php74"))]
     #[inline]
     pub fn get_var_flags(&self) -> u32 {
         unsafe { self.u2.var_flags }
@@ -392,16 +499,38 @@ impl Default for zend_string {
 
 /** string flags (zval.value->gc.u.flags) */
 /// allocated using malloc
+#[cfg(not(feature = "php74"))]
 pub const IS_STR_PERSISTENT: Flag = 1 << 0;
 /// interned string
+#[cfg(not(feature = "php74"))]
 pub const IS_STR_INTERNED: Flag = 1 << 1;
 /// relives request boundary
+#[cfg(not(feature = "php74"))]
 pub const IS_STR_PERMANENT: Flag = 1 << 2;
 /// constant index
+#[cfg(not(feature = "php74"))]
 pub const IS_STR_CONSTANT: Flag = 1 << 3;
 /// the same as IS_CONSTANT_UNQUALIFIED
+#[cfg(not(feature = "php74"))]
 pub const IS_STR_CONSTANT_UNQUALIFIED: Flag = 1 << 4;
 
+/** string flags (zval.value->gc.u.type_info), shared with the GC flags */
+/// allocated using malloc
+#[cfg(feature = "php74")]
+pub const IS_STR_PERSISTENT: Flag = GC_PERSISTENT;
+/// interned string
+#[cfg(feature = "php74")]
+pub const IS_STR_INTERNED: Flag = GC_IMMUTABLE;
+/// relives request boundary
+#[cfg(feature = "php74")]
+pub const IS_STR_PERMANENT: Flag = 1 << 8;
+
+/** array flags (zval.value->gc.u.flags) */
+/// shared memory array, never refcounted nor modified
+#[cfg(not(feature = "php74"))]
+pub const IS_ARRAY_IMMUTABLE: Flag = 1 << 1;
+#[cfg(feature = "php74")]
+pub const IS_ARRAY_IMMUTABLE: Flag = GC_IMMUTABLE;
 
 #[repr(C)]
 #[derive(Copy, Clone)]
@@ -476,9 +605,15 @@ pub union zend_array_u {
 #[derive(Debug, Default, Copy, Clone)]
 pub struct zend_array_u_v {
     pub flags: zend_uchar,
+    #[cfg(not(feature = "php74"))]
     pub nApplyCount: zend_uchar,
+    #[cfg(feature = "php74")]
+    pub _unused: zend_uchar,
     pub nIteratorsCount: zend_uchar,
+    #[cfg(not(feature = "php74"))]
     pub consistency: zend_uchar,
+    #[cfg(feature = "php74")]
+    pub _unused2: zend_uchar,
 }
 
 impl Default for zend_array_u {
@@ -494,6 +629,17 @@ impl Default for zend_array {
 }
 
 
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct zend_object {
+    pub gc: zend_refcounted_h,
+    pub handle: u32,
+    pub ce: *mut zend_class_entry,
+    pub handlers: *const zend_object_handlers,
+    pub properties: *mut HashTable,
+    pub properties_table: [zval; 1],
+}
+
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub struct zend_reference {
@@ -520,4 +666,39 @@ mod tests {
             mem::align_of::<zend_string>(), 8, concat!("Alignment of ", stringify!(zend_string))
         );
     }
+
+    #[test]
+    fn zval_layout() {
+        assert_eq!(mem::size_of::<zval>(), 16, concat!("Size of: ", stringify!(zval)));
+        assert_eq!(mem::size_of::<zval_v>(), 4, concat!("Size of: ", stringify!(zval_v)));
+        assert_eq!(mem::size_of::<zval_u2>(), 4, concat!("Size of: ", stringify!(zval_u2)));
+        assert_eq!(mem::size_of::<zend_refcounted_h>(), 8, concat!("Size of: ", stringify!(zend_refcounted_h)));
+    }
+
+    #[test]
+    fn zend_array_layout() {
+        assert_eq!(mem::size_of::<zend_array>(), 56, concat!("Size of: ", stringify!(zend_array)));
+        assert_eq!(mem::size_of::<zend_array_u_v>(), 4, concat!("Size of: ", stringify!(zend_array_u_v)));
+        assert_eq!(mem::size_of::<Bucket>(), 32, concat!("Size of: ", stringify!(Bucket)));
+    }
+
+    #[cfg(not(feature = "php74"))]
+    #[test]
+    fn type_constants() {
+        assert_eq!(IS_STRING_EX, 0x1406);
+        assert_eq!(IS_ARRAY_EX, 0x1c07);
+        assert_eq!(IS_OBJECT_EX, 0x0c08);
+        assert_eq!(IS_CONSTANT_EX, 0x150b);
+        assert_eq!(GC_ARRAY, 0x8007);
+    }
+
+    #[cfg(feature = "php74")]
+    #[test]
+    fn type_constants() {
+        assert_eq!(IS_STRING_EX, 0x0106);
+        assert_eq!(IS_ARRAY_EX, 0x0307);
+        assert_eq!(IS_OBJECT_EX, 0x0308);
+        assert_eq!(IS_CONSTANT_AST_EX, 0x010b);
+        assert_eq!(GC_STRING, 0x16);
+    }
 }
\ No newline at end of file
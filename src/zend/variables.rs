@@ -1,12 +1,19 @@
 use crate::zend::zval;
 
+// PHP 7.3 exports the destructors without the leading underscore
 extern {
     fn zend_print_variable(var: *const zval) -> usize;
+    #[cfg_attr(feature = "php74", link_name = "zval_ptr_dtor")]
     fn _zval_ptr_dtor(zval_ptr: *mut zval);
+    #[cfg(not(feature = "php74"))]
     fn _zval_internal_dtor_for_ptr(zvalue: *mut zval);
+    #[cfg_attr(feature = "php74", link_name = "zval_internal_dtor")]
     fn _zval_internal_dtor(zvalue: *mut zval);
+    #[cfg_attr(feature = "php74", link_name = "zval_internal_ptr_dtor")]
     fn _zval_internal_ptr_dtor(zvalue: *mut zval);
+    #[cfg(not(feature = "php74"))]
     fn _zval_dtor_wrapper(zvalue: *mut zval);
+    fn zval_add_ref(p: *mut zval);
 }
 
 #[inline]
@@ -15,6 +22,8 @@ pub fn print_variable(var: *const zval) -> usize {
 }
 
 pub const ZVAL_PTR_DTOR: unsafe extern fn(*mut zval) = _zval_ptr_dtor;
+/// Copy constructor for values shared between arrays
+pub const ZVAL_ADD_REF: unsafe extern fn(*mut zval) = zval_add_ref;
 
 impl zval {
     #[inline]
@@ -32,6 +41,7 @@ impl zval {
         unsafe { _zval_internal_ptr_dtor(zvalue) };
     }
 
+    #[cfg(not(feature = "php74"))]
     #[inline]
     pub fn dtor_wrapper(zvalue: *mut Self) {
         unsafe { _zval_dtor_wrapper(zvalue) };